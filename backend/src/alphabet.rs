@@ -2,6 +2,8 @@
 
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 /// Kannada script character definitions
 pub struct KannadaScript;
 
@@ -33,9 +35,71 @@ impl KannadaScript {
     pub fn punctuation() -> &'static [char] { &[' ', '.', ',', '!', '?', '।'] }
 }
 
+/// Broad shape of a grapheme cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterKind {
+    Space,
+    Punctuation,
+    /// Independent vowel, with or without a modifier (ಅ, ಅಂ)
+    Vowel,
+    /// One consonant with the inherent vowel, a matra or a modifier (ಕ, ಕಾ, ಕಂ)
+    Consonant,
+    /// Consonant with halant (ಕ್)
+    DeadConsonant,
+    /// Two consonants joined by halant, with any vowel sign or modifier (ಕ್ಷ, ಕ್ಕಿ)
+    Conjunct,
+    /// Conjunct ending in halant (ರ್ನ್)
+    DeadConjunct,
+}
+
+impl ClusterKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "space" => Some(ClusterKind::Space),
+            "punctuation" => Some(ClusterKind::Punctuation),
+            "vowel" => Some(ClusterKind::Vowel),
+            "consonant" => Some(ClusterKind::Consonant),
+            "dead_consonant" => Some(ClusterKind::DeadConsonant),
+            "conjunct" => Some(ClusterKind::Conjunct),
+            "dead_conjunct" => Some(ClusterKind::DeadConjunct),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClusterKind::Space => "space",
+            ClusterKind::Punctuation => "punctuation",
+            ClusterKind::Vowel => "vowel",
+            ClusterKind::Consonant => "consonant",
+            ClusterKind::DeadConsonant => "dead_consonant",
+            ClusterKind::Conjunct => "conjunct",
+            ClusterKind::DeadConjunct => "dead_conjunct",
+        }
+    }
+
+    fn of(cluster: &str) -> Self {
+        let halant = KannadaScript::halant();
+        let halants = cluster.chars().filter(|&c| c == halant).count();
+        match cluster.chars().next() {
+            Some(' ') => ClusterKind::Space,
+            Some(c) if KannadaScript::punctuation().contains(&c) => ClusterKind::Punctuation,
+            Some(c) if KannadaScript::vowels().contains(&c) => ClusterKind::Vowel,
+            _ => match (halants, cluster.ends_with(halant)) {
+                (0, _) => ClusterKind::Consonant,
+                (1, true) => ClusterKind::DeadConsonant,
+                (1, false) => ClusterKind::Conjunct,
+                _ => ClusterKind::DeadConjunct,
+            },
+        }
+    }
+}
+
 /// Alphabet of valid Kannada grapheme clusters
 pub struct GraphemeAlphabet {
     clusters: Vec<String>,
+    kinds: Vec<ClusterKind>,
     cluster_to_index: HashMap<String, usize>,
     max_cluster_chars: usize,
 }
@@ -120,7 +184,9 @@ impl GraphemeAlphabet {
             .map(|(i, s)| (s.clone(), i))
             .collect();
 
-        GraphemeAlphabet { clusters, cluster_to_index, max_cluster_chars }
+        let kinds = clusters.iter().map(|s| ClusterKind::of(s)).collect();
+
+        GraphemeAlphabet { clusters, kinds, cluster_to_index, max_cluster_chars }
     }
 
     #[inline]
@@ -131,6 +197,16 @@ impl GraphemeAlphabet {
         self.clusters.get(index).map(|s| s.as_str())
     }
 
+    #[inline]
+    pub fn kind(&self, index: usize) -> Option<ClusterKind> {
+        self.kinds.get(index).copied()
+    }
+
+    /// Indices of every cluster of the given kind
+    pub fn indices_of_kind(&self, kind: ClusterKind) -> Vec<usize> {
+        (0..self.size()).filter(|&i| self.kinds[i] == kind).collect()
+    }
+
     #[inline]
     pub fn index_of(&self, cluster: &str) -> Option<usize> {
         self.cluster_to_index.get(cluster).copied()
@@ -0,0 +1,180 @@
+//! Cluster frequency statistics over pages
+//!
+//! Every figure is set against what uniformly random pages would give, since
+//! that is exactly what the library is: each cluster expected
+//! `clusters / N` times, each [`ClusterKind`] in proportion to its share of
+//! the alphabet.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::alphabet::{ClusterKind, GraphemeAlphabet};
+
+const KINDS: [ClusterKind; 7] = [
+    ClusterKind::Space,
+    ClusterKind::Punctuation,
+    ClusterKind::Vowel,
+    ClusterKind::Consonant,
+    ClusterKind::DeadConsonant,
+    ClusterKind::Conjunct,
+    ClusterKind::DeadConjunct,
+];
+
+/// How often one cluster occurs
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterCount {
+    pub index: usize,
+    pub cluster: String,
+    pub count: usize,
+}
+
+/// Clusters of one kind, observed and expected for uniform pages
+#[derive(Debug, Clone, Serialize)]
+pub struct KindCount {
+    pub kind: ClusterKind,
+    pub count: usize,
+    pub share: f64,
+    pub expected_share: f64,
+}
+
+/// The longest stretch of repeats, within one page
+#[derive(Debug, Clone, Serialize)]
+pub struct Run {
+    /// The repeated cluster, or the shared kind's name
+    pub value: String,
+    pub length: usize,
+    /// Which of the analysed pages (from 0) and the cluster position on it
+    pub page: usize,
+    pub position: usize,
+}
+
+/// Frequency statistics for a set of pages
+///
+/// `entropy_bits` is the plug-in Shannon entropy of the observed clusters. It
+/// can't exceed `sample_limit_bits`, log2 of the number of clusters analysed,
+/// so a single page of uniform clusters sits well below `max_entropy_bits`
+/// (log2 N) without that meaning anything. `chi_square` against the uniform
+/// distribution has N - 1 degrees of freedom.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterStats {
+    pub pages: usize,
+    pub clusters: usize,
+    pub distinct_clusters: usize,
+    pub alphabet_size: usize,
+    pub expected_count: f64,
+    pub most_common: Vec<ClusterCount>,
+    pub kinds: Vec<KindCount>,
+    pub entropy_bits: f64,
+    pub max_entropy_bits: f64,
+    pub sample_limit_bits: f64,
+    pub chi_square: f64,
+    pub longest_cluster_run: Option<Run>,
+    pub longest_kind_run: Option<Run>,
+}
+
+impl ClusterStats {
+    /// Statistics over `pages` (cluster indices), listing the `top` most common clusters
+    pub fn compute(pages: &[&[usize]], alphabet: &GraphemeAlphabet, top: usize) -> Self {
+        let alphabet_size = alphabet.size();
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for &index in pages.iter().copied().flatten() {
+            *counts.entry(index).or_insert(0) += 1;
+        }
+        let clusters: usize = counts.values().sum();
+        let total = clusters as f64;
+
+        let mut most_common: Vec<ClusterCount> = counts
+            .iter()
+            .map(|(&index, &count)| ClusterCount {
+                index,
+                cluster: alphabet.get(index).unwrap_or("").to_string(),
+                count,
+            })
+            .collect();
+        most_common.sort_by(|a, b| b.count.cmp(&a.count).then(a.index.cmp(&b.index)));
+        most_common.truncate(top);
+
+        let kinds = KINDS
+            .iter()
+            .map(|&kind| {
+                let count = counts
+                    .iter()
+                    .filter(|&(&index, _)| alphabet.kind(index) == Some(kind))
+                    .map(|(_, &count)| count)
+                    .sum();
+                KindCount {
+                    kind,
+                    count,
+                    share: if clusters == 0 { 0.0 } else { count as f64 / total },
+                    expected_share: alphabet.indices_of_kind(kind).len() as f64 / alphabet_size as f64,
+                }
+            })
+            .collect();
+
+        let entropy_bits = -counts
+            .values()
+            .map(|&count| {
+                let p = count as f64 / total;
+                p * p.log2()
+            })
+            .sum::<f64>();
+
+        // Σ (o - e)² / e over every cluster, including the unseen ones
+        let expected_count = total / alphabet_size as f64;
+        let chi_square = if clusters == 0 {
+            0.0
+        } else {
+            let squares: f64 = counts.values().map(|&count| (count as f64).powi(2)).sum();
+            squares / expected_count - total
+        };
+
+        ClusterStats {
+            pages: pages.len(),
+            clusters,
+            distinct_clusters: counts.len(),
+            alphabet_size,
+            expected_count,
+            most_common,
+            kinds,
+            entropy_bits: entropy_bits.max(0.0),
+            max_entropy_bits: (alphabet_size as f64).log2(),
+            sample_limit_bits: (clusters.clamp(1, alphabet_size) as f64).log2(),
+            chi_square,
+            longest_cluster_run: Self::longest_run(pages, |index| index, |index| {
+                alphabet.get(index).unwrap_or("").to_string()
+            }),
+            longest_kind_run: Self::longest_run(pages, |index| alphabet.kind(index), |kind| {
+                kind.map_or_else(String::new, |kind| kind.name().to_string())
+            }),
+        }
+    }
+
+    /// Longest run of clusters with equal `key`, first one found on ties
+    fn longest_run<K: PartialEq + Copy>(
+        pages: &[&[usize]],
+        key: impl Fn(usize) -> K,
+        describe: impl Fn(K) -> String,
+    ) -> Option<Run> {
+        let mut best: Option<(K, usize, usize, usize)> = None;
+
+        for (page, indices) in pages.iter().enumerate() {
+            let mut start = 0;
+            while start < indices.len() {
+                let value = key(indices[start]);
+                let length = indices[start..].iter().take_while(|&&i| key(i) == value).count();
+                if best.is_none_or(|(_, longest, _, _)| length > longest) {
+                    best = Some((value, length, page, start));
+                }
+                start += length;
+            }
+        }
+
+        best.map(|(value, length, page, position)| Run {
+            value: describe(value),
+            length,
+            page,
+            position,
+        })
+    }
+}
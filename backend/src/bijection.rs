@@ -4,7 +4,7 @@ use num_bigint::{BigUint, BigInt};
 use num_traits::{Zero, One, ToPrimitive, Signed};
 use num_integer::Integer;
 
-use crate::constants::CLUSTERS_PER_PAGE;
+use crate::geometry::LibraryGeometry;
 
 /// Bijective mapping between content and addresses
 pub struct BijectionEngine {
@@ -15,9 +15,9 @@ pub struct BijectionEngine {
 }
 
 impl BijectionEngine {
-    pub fn new(alphabet_size: usize) -> Self {
+    pub fn new(alphabet_size: usize, geometry: &LibraryGeometry) -> Self {
         let alphabet_size = BigUint::from(alphabet_size);
-        let modulus = alphabet_size.pow(CLUSTERS_PER_PAGE as u32);
+        let modulus = alphabet_size.pow(geometry.clusters_per_page as u32);
 
         let multiplier = Self::generate_coprime(&modulus);
         let inverse = Self::mod_inverse(&multiplier, &modulus)
@@ -83,6 +83,113 @@ impl BijectionEngine {
         (address * &self.inverse) % &self.modulus
     }
 
+    /// Smallest address whose content has `window` as the `width` digits ending
+    /// `below` digits from the right (None only if `window` doesn't fit)
+    ///
+    /// Content = address × inverse mod N^400, and its lowest `below + width`
+    /// digits only depend on address × inverse mod N^(below + width). Those digits
+    /// hold `window` exactly when that residue lies in one interval, so this is
+    /// "smallest x with lo ≤ a·x mod m ≤ hi", solved by [`Self::smallest_in_range`].
+    pub fn smallest_address_with(&self, window: &BigUint, width: u32, below: u32) -> Option<BigUint> {
+        let (m, inverse, lo, hi) = self.window_residues(window, width, below)?;
+        Self::smallest_in_range(&inverse, &m, &lo, &hi)
+    }
+
+    /// (m, inverse mod m, lo, hi): an address holds `window` `below` digits from
+    /// the right exactly when lo ≤ address × inverse mod m ≤ hi
+    pub(crate) fn window_residues(
+        &self,
+        window: &BigUint,
+        width: u32,
+        below: u32,
+    ) -> Option<(BigUint, BigUint, BigUint, BigUint)> {
+        let low_digits = self.alphabet_size.pow(below);
+        let m = self.alphabet_size.pow(below + width);
+        if m > self.modulus || window * &low_digits >= m {
+            return None;
+        }
+
+        let lo = window * &low_digits;
+        let hi = &lo + &low_digits - BigUint::one();
+        let inverse = &self.inverse % &m;
+        Some((m, inverse, lo, hi))
+    }
+
+    /// Smallest x ≥ 0 with lo ≤ (base + a·x mod m) ≤ hi, for lo ≤ hi < m
+    pub fn smallest_from(
+        base: &BigUint,
+        a: &BigUint,
+        m: &BigUint,
+        lo: &BigUint,
+        hi: &BigUint,
+    ) -> Option<BigUint> {
+        if lo > hi || hi >= m {
+            return None;
+        }
+
+        // Shift the interval by −base; it may wrap past zero into two pieces
+        let shift = m - base % m;
+        let lo = (lo + &shift) % m;
+        let hi = (hi + &shift) % m;
+        if lo <= hi {
+            Self::smallest_in_range(a, m, &lo, &hi)
+        } else {
+            let upper = Self::smallest_in_range(a, m, &lo, &(m - BigUint::one()));
+            let lower = Self::smallest_in_range(a, m, &BigUint::zero(), &hi);
+            match (upper, lower) {
+                (Some(u), Some(l)) => Some(u.min(l)),
+                (u, l) => u.or(l),
+            }
+        }
+    }
+
+    /// Smallest x ≥ 0 with lo ≤ (a·x mod m) ≤ hi, for lo ≤ hi < m
+    ///
+    /// Euclid-style reduction: if no multiple of a falls in [lo, hi], then
+    /// a·x − m·y ∈ [lo, hi] is rewritten as m·y mod a ∈ [−hi mod a, −lo mod a],
+    /// a smaller problem of the same shape. Runs in O(log m) steps, iteratively
+    /// so that 6,000-bit moduli don't exhaust the stack.
+    pub fn smallest_in_range(a: &BigUint, m: &BigUint, lo: &BigUint, hi: &BigUint) -> Option<BigUint> {
+        if lo > hi || hi >= m {
+            return None;
+        }
+
+        let mut frames: Vec<(BigUint, BigUint, BigUint, BigUint)> = Vec::new();
+        let (mut a, mut m, mut lo, mut hi) = (a % m, m.clone(), lo.clone(), hi.clone());
+
+        let mut x = loop {
+            if lo.is_zero() {
+                break BigUint::zero();
+            }
+            if a.is_zero() {
+                return None;
+            }
+            let k = lo.div_ceil(&a);
+            if &a * &k <= hi {
+                break k;
+            }
+
+            // No multiple of a in [lo, hi], so hi − lo < a and the reduced interval doesn't wrap
+            let next = (
+                &m % &a,
+                a.clone(),
+                (&a - &hi % &a) % &a,
+                (&a - &lo % &a) % &a,
+            );
+            frames.push((a, m, lo, hi));
+            (a, m, lo, hi) = next;
+        };
+
+        while let Some((a, m, lo, hi)) = frames.pop() {
+            let y = x;
+            x = (&lo + &m * &y).div_ceil(&a);
+            if &a * &x - &m * &y > hi {
+                return None;
+            }
+        }
+        Some(x)
+    }
+
     /// Convert cluster indices to BigUint (base-N encoding)
     pub fn indices_to_biguint(&self, indices: &[usize]) -> BigUint {
         let mut result = BigUint::zero();
@@ -104,6 +211,11 @@ impl BijectionEngine {
     }
 
     pub fn modulus(&self) -> &BigUint { &self.modulus }
+
+    pub fn multiplier(&self) -> &BigUint { &self.multiplier }
+
+    /// Content difference between consecutive addresses
+    pub fn inverse(&self) -> &BigUint { &self.inverse }
     
     pub fn alphabet_size(&self) -> &BigUint { &self.alphabet_size }
 }
\ No newline at end of file
@@ -0,0 +1,180 @@
+//! Command-line tools for ಅಕ್ಷರ ಮಂಟಪ
+//!
+//! ```text
+//! akshara encode [FILE]          Hide a file (or stdin); prints one address per line
+//! akshara decode [ADDRESS...]    Recover hidden bytes to stdout (addresses from stdin if none)
+//! akshara stats [ADDRESS] [--book] [--sample N] [--seed S] [--top K]
+//!                                 Cluster statistics for a page, its book or random pages
+//! ```
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use akshara_mantapa::{rng_from_seed, ClusterStats, LibraryOfBabel, Location, Page};
+
+const USAGE: &str = "\
+Usage:
+  akshara encode [FILE]          Hide a file (or stdin); prints one address per line
+  akshara decode [ADDRESS...]    Recover hidden bytes to stdout (addresses from stdin if none)
+  akshara stats [ADDRESS] [--book] [--sample N] [--seed S] [--top K]
+                                 Cluster statistics for a page, its book or random pages";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let library = LibraryOfBabel::new();
+
+    let result = match args.first().map(String::as_str) {
+        Some("encode") => encode(&library, &args[1..]),
+        Some("decode") => decode(&library, &args[1..]),
+        Some("stats") => stats(&library, &args[1..]),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn encode(library: &LibraryOfBabel, args: &[String]) -> Result<(), String> {
+    let data = match args {
+        [] => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data).map_err(|e| format!("Reading stdin: {}", e))?;
+            data
+        }
+        [path] => fs::read(path).map_err(|e| format!("Reading {}: {}", path, e))?,
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let locations = library
+        .encode_file(&data)
+        .ok_or("Pages in this library can't hold any bytes")?;
+    for location in locations {
+        println!("{}", location.raw_hex);
+    }
+    Ok(())
+}
+
+fn decode(library: &LibraryOfBabel, args: &[String]) -> Result<(), String> {
+    let addresses: Vec<String> = if args.is_empty() {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input).map_err(|e| format!("Reading stdin: {}", e))?;
+        input.split_whitespace().map(str::to_string).collect()
+    } else {
+        args.to_vec()
+    };
+
+    let locations = addresses
+        .iter()
+        .map(|address| {
+            library
+                .parse_address(address)
+                .ok_or_else(|| format!("Invalid address: {}", address))
+        })
+        .collect::<Result<Vec<Location>, String>>()?;
+
+    let data = library
+        .decode_file(&locations)
+        .ok_or("These pages don't hold encoded bytes")?;
+    io::stdout().write_all(&data).map_err(|e| format!("Writing stdout: {}", e))
+}
+
+fn stats(library: &LibraryOfBabel, args: &[String]) -> Result<(), String> {
+    let mut address = None;
+    let mut book = false;
+    let mut sample = 100;
+    let mut seed = None;
+    let mut top = 10;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| {
+            args.next()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| format!("{} needs a number", name))
+        };
+        match arg.as_str() {
+            "--book" => book = true,
+            "--sample" => sample = number("--sample")?,
+            "--seed" => seed = Some(number("--seed")? as u64),
+            "--top" => top = number("--top")?,
+            _ if address.is_none() && !arg.starts_with("--") => address = Some(arg.as_str()),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+
+    let pages: Vec<Page> = match address {
+        Some(address) => {
+            let location = library
+                .parse_address(address)
+                .ok_or_else(|| format!("Invalid address: {}", address))?;
+            if book {
+                library.generate_book(&location).pages
+            } else {
+                vec![library.generate_page(&location)]
+            }
+        }
+        None => {
+            let mut rng = rng_from_seed(seed);
+            (0..sample.max(1)).map(|_| library.random_page_with(&mut rng)).collect()
+        }
+    };
+
+    print_stats(&library.cluster_stats(&pages, top));
+    Ok(())
+}
+
+fn print_stats(stats: &ClusterStats) {
+    println!("Pages:       {}", stats.pages);
+    println!(
+        "Clusters:    {} ({} distinct of {})",
+        stats.clusters, stats.distinct_clusters, stats.alphabet_size
+    );
+    println!(
+        "Entropy:     {:.3} bits (at most {:.3} for this many clusters, {:.3} for the alphabet)",
+        stats.entropy_bits, stats.sample_limit_bits, stats.max_entropy_bits
+    );
+    println!(
+        "Chi-square:  {:.1} (about {} for uniform pages)",
+        stats.chi_square,
+        stats.alphabet_size - 1
+    );
+
+    println!();
+    println!("{:<16} {:>9} {:>8} {:>9}", "Kind", "Count", "Share", "Uniform");
+    for kind in &stats.kinds {
+        println!(
+            "{:<16} {:>9} {:>7.3}% {:>8.3}%",
+            kind.kind.name(),
+            kind.count,
+            kind.share * 100.0,
+            kind.expected_share * 100.0
+        );
+    }
+
+    println!();
+    println!("Most common (uniform expectation {:.4} each):", stats.expected_count);
+    for cluster in &stats.most_common {
+        println!("  {:>6}  {:>8}  {}", cluster.index, cluster.count, cluster.cluster);
+    }
+
+    println!();
+    for (label, run) in [("cluster", &stats.longest_cluster_run), ("kind", &stats.longest_kind_run)] {
+        if let Some(run) = run {
+            println!(
+                "Longest {} run: {} x '{}' on page {} at cluster {}",
+                label, run.length, run.value, run.page, run.position
+            );
+        }
+    }
+}
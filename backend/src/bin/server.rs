@@ -1,20 +1,23 @@
     //! Axum server for ಅಕ್ಷರ ಮಂಟಪ API
 
     use axum::{
+        body::Bytes,
         extract::{Query, State},
         http::{header, Method, StatusCode},
         response::{IntoResponse, Json},
-        routing::get,
+        routing::{get, post},
         Router,
     };
+    use num_bigint::BigUint;
     use serde::{Deserialize, Serialize};
     use std::sync::Arc;
+    use std::time::{SystemTime, UNIX_EPOCH};
     use tower_http::cors::{Any, CorsLayer};
 
     use akshara_mantapa::{
-        HierarchicalAddress, LibraryOfBabel, Location,
-        CLUSTERS_PER_PAGE, PAGES_PER_BOOK, BOOKS_PER_SHELF,
-        SHELVES_PER_WALL, WALLS_PER_ROOM,
+        rng_from_seed, AddressConstraint, CalendarDate, CoherenceModel, ComposeError,
+        ConstrainedSearch, Direction, Filler, Layout, LibraryOfBabel, Level, LineSpan, Location,
+        MatchSpan, Page, PageScore, Placement, SearchResult, Verification, IST_OFFSET_SECONDS,
     };
 
     // ============================================================================
@@ -24,6 +27,8 @@
     #[derive(Clone)]
     struct AppState {
         library: Arc<LibraryOfBabel>,
+        /// Word list model for /api/coherent, from the file named by AKSHARA_WORDLIST
+        coherence: Option<Arc<CoherenceModel>>,
     }
 
     // ============================================================================
@@ -35,11 +40,196 @@
         address: String,
     }
 
+    /// A page, laid out by `layout` (fixed, wrap, justified or verse) at `width`
+    /// clusters per line (default: the library's line width)
+    #[derive(Deserialize)]
+    struct PageQuery {
+        address: String,
+        layout: Option<String>,
+        width: Option<usize>,
+    }
+
+    impl PageQuery {
+        /// None for an unknown layout name
+        fn layout(&self, library: &LibraryOfBabel) -> Option<Layout> {
+            let width = self.width.unwrap_or(library.geometry().clusters_per_line);
+            match self.layout.as_deref() {
+                None => Some(Layout::Fixed),
+                Some(name) => Layout::from_name(name, width),
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct WalkQuery {
+        address: String,
+        direction: String,
+    }
+
     #[derive(Deserialize)]
     struct SearchQuery {
         q: String,
     }
 
+    /// Optional seed for reproducible randomness ("run with seed X"), and an
+    /// optional `address` + `level` (book, shelf, wall or room) to stay within
+    #[derive(Deserialize)]
+    struct RandomQuery {
+        seed: Option<u64>,
+        address: Option<String>,
+        level: Option<String>,
+    }
+
+    /// Page of the day: `date` as YYYY-MM-DD (default today in IST) and an optional `salt`
+    #[derive(Deserialize)]
+    struct DailyQuery {
+        date: Option<String>,
+        salt: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct RandomSearchQuery {
+        q: String,
+        seed: Option<u64>,
+    }
+
+    /// Placement for /api/search-at: `position`, or `line` with `column` or
+    /// `align=centre|end`, or `align=page-end`; `filler` is blank (default),
+    /// uniform, kannada or repeat, reproducible with `seed`. Also used by
+    /// /api/search-pattern, where `q` is a pattern and `random` picks a random match
+    #[derive(Deserialize)]
+    struct SearchAtQuery {
+        q: String,
+        position: Option<usize>,
+        line: Option<usize>,
+        column: Option<usize>,
+        align: Option<String>,
+        filler: Option<String>,
+        seed: Option<u64>,
+        random: Option<bool>,
+    }
+
+    impl SearchAtQuery {
+        fn placement(&self) -> Option<Placement> {
+            match (self.position, self.line, self.align.as_deref()) {
+                (Some(position), None, None) => Some(Placement::Position { position }),
+                (None, None, Some("page-end")) => Some(Placement::PageEnd),
+                (None, Some(line), Some("centre")) => Some(Placement::LineCentre { line }),
+                (None, Some(line), Some("end")) => Some(Placement::LineEnd { line }),
+                (None, Some(line), None) => Some(Placement::LineColumn {
+                    line,
+                    column: self.column.unwrap_or(0),
+                }),
+                _ => None,
+            }
+        }
+
+        fn filler(&self) -> Option<Filler> {
+            match self.filler.as_deref() {
+                None => Some(Filler::Blank),
+                Some(name) => Filler::from_name(name),
+            }
+        }
+    }
+
+    /// Constraints for /api/search-constrained: any of the address levels
+    /// (mandira as hex or Kannada), and optionally the position of `q`
+    #[derive(Deserialize)]
+    struct ConstrainedQuery {
+        q: String,
+        mandira: Option<String>,
+        gode: Option<u32>,
+        patti: Option<u32>,
+        pustaka: Option<u32>,
+        puta: Option<u32>,
+        position: Option<usize>,
+    }
+
+    /// Search budget for /api/search-shortest: query positions to try (default 50, at most 100)
+    #[derive(Deserialize)]
+    struct ShortestQuery {
+        q: String,
+        budget: Option<usize>,
+    }
+
+    /// Random pages to score for /api/coherent, and how many of the best to return
+    #[derive(Deserialize)]
+    struct CoherentQuery {
+        samples: Option<usize>,
+        keep: Option<usize>,
+        seed: Option<u64>,
+    }
+
+    /// Statistics for the page at `address` (or its whole book with `scope=book`),
+    /// or for `sample` random pages when no address is given
+    #[derive(Deserialize)]
+    struct StatsQuery {
+        address: Option<String>,
+        scope: Option<String>,
+        sample: Option<usize>,
+        seed: Option<u64>,
+        top: Option<usize>,
+    }
+
+    /// Pagination for /api/occurrences: resume at `token` (decimal), up to `limit` pages
+    #[derive(Deserialize)]
+    struct OccurrencesQuery {
+        q: String,
+        token: Option<String>,
+        limit: Option<usize>,
+    }
+
+    /// Addresses for /api/decode, comma-separated in the order /api/encode gave them
+    #[derive(Deserialize)]
+    struct DecodeQuery {
+        addresses: String,
+    }
+
+    /// Body for POST /api/compose: fragments placed by `position` or by
+    /// `line` and `column`, with the rest of the page from `filler`
+    #[derive(Deserialize)]
+    struct ComposeRequest {
+        fragments: Vec<FragmentBody>,
+        filler: Option<String>,
+        seed: Option<u64>,
+    }
+
+    #[derive(Deserialize)]
+    struct FragmentBody {
+        text: String,
+        position: Option<usize>,
+        line: Option<usize>,
+        column: Option<usize>,
+    }
+
+    impl FragmentBody {
+        fn placement(&self) -> Option<Placement> {
+            match (self.position, self.line) {
+                (Some(position), None) => Some(Placement::Position { position }),
+                (None, Some(line)) => Some(Placement::LineColumn {
+                    line,
+                    column: self.column.unwrap_or(0),
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    /// Body for POST /api/hide: a message to encrypt onto a fresh page
+    #[derive(Deserialize)]
+    struct HideRequest {
+        text: String,
+        passphrase: String,
+        seed: Option<u64>,
+    }
+
+    /// Body for POST /api/reveal
+    #[derive(Deserialize)]
+    struct RevealRequest {
+        address: String,
+        passphrase: String,
+    }
+
     #[derive(Deserialize)]
     struct VerifyParams {
         address: String,
@@ -56,16 +246,17 @@
         hierarchical: HierarchicalDisplay,
         content: String,
         formatted_content: String,
+        lines: Vec<LineSpan>,
     }
 
     #[derive(Serialize)]
     struct HierarchicalDisplay {
         mandira_hex: String,
         mandira_kannada: Option<String>,
-        gode: u8,
-        patti: u8,
-        pustaka: u8,
-        puta: u16,
+        gode: u32,
+        patti: u32,
+        pustaka: u32,
+        puta: u32,
         display_string: String,
     }
 
@@ -75,6 +266,69 @@
         found: bool,
         location: Option<LocationResponse>,
         page_preview: Option<String>,
+        span: Option<MatchSpan>,
+    }
+
+    /// `outcome` is found, impossible (no such page exists), budget_exceeded
+    /// (too loosely constrained to decide) or invalid
+    #[derive(Serialize)]
+    struct ConstrainedResponse {
+        outcome: &'static str,
+        #[serde(flatten)]
+        result: SearchResponse,
+    }
+
+    /// Pattern search: `query` in the flattened result is the text that was chosen
+    #[derive(Serialize)]
+    struct PatternSearchResponse {
+        pattern: String,
+        error: Option<String>,
+        #[serde(flatten)]
+        result: SearchResponse,
+    }
+
+    #[derive(Serialize)]
+    struct OccurrenceResponse {
+        token: String,
+        #[serde(flatten)]
+        result: SearchResponse,
+    }
+
+    /// A page of occurrences; `next_token` continues after the last one
+    #[derive(Serialize)]
+    struct OccurrencesResponse {
+        query: String,
+        occurrences: Vec<OccurrenceResponse>,
+        next_token: Option<String>,
+    }
+
+    /// Pages holding an uploaded file, in order (not consecutive pages)
+    #[derive(Serialize)]
+    struct EncodeResponse {
+        length: usize,
+        bytes_per_page: usize,
+        addresses: Vec<LocationResponse>,
+    }
+
+    #[derive(Serialize)]
+    struct DailyResponse {
+        date: String,
+        #[serde(flatten)]
+        page: PageResponse,
+    }
+
+    /// The best of `samples` random pages, most coherent first
+    #[derive(Serialize)]
+    struct CoherentResponse {
+        samples: usize,
+        pages: Vec<CoherentPageResponse>,
+    }
+
+    #[derive(Serialize)]
+    struct CoherentPageResponse {
+        location: LocationResponse,
+        content: String,
+        score: PageScore,
     }
 
     #[derive(Serialize)]
@@ -95,12 +349,46 @@
         address_bits: u64,
     }
 
+    #[derive(Serialize)]
+    struct BookResponse {
+        location: LocationResponse,
+        title: String,
+        pages: Vec<PageResponse>,
+    }
+
+    #[derive(Serialize)]
+    struct SpineResponse {
+        pustaka: u32,
+        title: String,
+        location: LocationResponse,
+    }
+
+    #[derive(Serialize)]
+    struct ShelfResponse {
+        gode: u32,
+        patti: u32,
+        books: Vec<SpineResponse>,
+    }
+
+    #[derive(Serialize)]
+    struct NeighboursResponse {
+        location: LocationResponse,
+        up: LocationResponse,
+        down: LocationResponse,
+        left: LocationResponse,
+        right: LocationResponse,
+    }
+
     #[derive(Serialize)]
     struct VerifyResponse {
         verified: bool,
         address: String,
         expected_text: String,
         actual_start: String,
+        /// Occurrences anywhere on the page, a diff and the bijection proof
+        /// (absent if the text isn't made of alphabet clusters)
+        #[serde(flatten)]
+        details: Option<Verification>,
     }
 
     // ============================================================================
@@ -111,11 +399,7 @@
         library: &LibraryOfBabel,
         location: &Location,
     ) -> HierarchicalDisplay {
-        let mandira_kannada = if location.hierarchical.mandira.bits() < 10000 {
-            Some(library.mandira_as_kannada(&location.hierarchical.mandira))
-        } else {
-            None
-        };
+        let mandira_kannada = library.mandira_as_kannada(&location.hierarchical.mandira);
 
         HierarchicalDisplay {
             mandira_hex: location.hierarchical.mandira_hex(),
@@ -128,6 +412,63 @@
         }
     }
 
+    fn make_location_response(library: &LibraryOfBabel, location: &Location) -> LocationResponse {
+        LocationResponse {
+            raw_address: location.raw_hex.clone(),
+            hierarchical: make_hierarchical_display(library, location),
+        }
+    }
+
+    fn make_search_response(
+        library: &LibraryOfBabel,
+        query: String,
+        result: Option<SearchResult>,
+    ) -> SearchResponse {
+        match result {
+            Some(result) => {
+                let page = library.generate_page(&result.location);
+                let preview: String = page.content.chars().take(80).collect();
+
+                SearchResponse {
+                    query,
+                    found: true,
+                    location: Some(make_location_response(library, &result.location)),
+                    page_preview: Some(preview),
+                    span: Some(result.span),
+                }
+            }
+            None => SearchResponse {
+                query,
+                found: false,
+                location: None,
+                page_preview: None,
+                span: None,
+            },
+        }
+    }
+
+    /// Run slow library work on tokio's blocking pool, off the async workers
+    async fn run_blocking<T: Send + 'static>(
+        work: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, axum::response::Response> {
+        tokio::task::spawn_blocking(work).await.map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": "Internal error" })),
+            ).into_response()
+        })
+    }
+
+    fn make_page_response(library: &LibraryOfBabel, page: Page) -> PageResponse {
+        PageResponse {
+            raw_address: page.location.raw_hex.clone(),
+            hierarchical: make_hierarchical_display(library, &page.location),
+            content: page.content,
+            formatted_content: page.formatted_content,
+            lines: page.lines,
+        }
+    }
+
     // ============================================================================
     // Handlers
     // ============================================================================
@@ -144,42 +485,63 @@
 
     async fn get_library_info(State(state): State<AppState>) -> impl IntoResponse {
         let alphabet_size = state.library.alphabet_size();
+        let geometry = state.library.geometry();
+        let clusters_per_page = geometry.clusters_per_page;
 
         let total_pages = format!(
             "{}^{} ≈ 10^{}",
             alphabet_size,
-            CLUSTERS_PER_PAGE,
-            ((CLUSTERS_PER_PAGE as f64) * (alphabet_size as f64).log10()).round() as u64
+            clusters_per_page,
+            ((clusters_per_page as f64) * (alphabet_size as f64).log10()).round() as u64
         );
 
-        let address_bits = ((CLUSTERS_PER_PAGE as f64) * (alphabet_size as f64).log2()).ceil() as u64;
+        let address_bits = ((clusters_per_page as f64) * (alphabet_size as f64).log2()).ceil() as u64;
 
         Json(LibraryInfo {
             alphabet_size,
-            clusters_per_page: CLUSTERS_PER_PAGE,
-            pages_per_book: PAGES_PER_BOOK,
-            books_per_shelf: BOOKS_PER_SHELF,
-            shelves_per_wall: SHELVES_PER_WALL,
-            walls_per_room: WALLS_PER_ROOM,
+            clusters_per_page,
+            pages_per_book: geometry.pages_per_book,
+            books_per_shelf: geometry.books_per_shelf,
+            shelves_per_wall: geometry.shelves_per_wall,
+            walls_per_room: geometry.walls_per_room,
             total_pages,
             address_bits,
         })
     }
 
-    async fn get_random_page(State(state): State<AppState>) -> impl IntoResponse {
-        let page = state.library.random_page();
+    async fn get_random_page(
+        State(state): State<AppState>,
+        Query(query): Query<RandomQuery>,
+    ) -> impl IntoResponse {
+        let mut rng = rng_from_seed(query.seed);
 
-        Json(PageResponse {
-            raw_address: page.location.raw_hex.clone(),
-            hierarchical: make_hierarchical_display(&state.library, &page.location),
-            content: page.content,
-            formatted_content: page.formatted_content,
-        })
+        let location = match (&query.address, &query.level) {
+            (None, None) => Some(state.library.random_location_with(&mut rng)),
+            (Some(address), Some(level)) => state.library
+                .parse_address(address)
+                .zip(Level::from_name(level))
+                .and_then(|(scope, level)| {
+                    state.library.random_location_in_with(&scope, level, &mut rng)
+                }),
+            _ => None,
+        };
+
+        let Some(location) = location else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "Give both address and level (book, shelf, wall or room) to restrict the random page"
+                })),
+            ).into_response();
+        };
+
+        let page = state.library.generate_page(&location);
+        Json(make_page_response(&state.library, page)).into_response()
     }
 
     async fn get_page_by_address(
         State(state): State<AppState>,
-        Query(query): Query<AddressQuery>,
+        Query(query): Query<PageQuery>,
     ) -> impl IntoResponse {
         let location = match state.library.parse_address(&query.address) {
             Some(loc) => loc,
@@ -193,19 +555,20 @@
             }
         };
 
-        let page = state.library.generate_page(&location);
+        let Some(layout) = query.layout(&state.library) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Layout must be fixed, wrap, justified or verse" })),
+            ).into_response();
+        };
 
-        Json(PageResponse {
-            raw_address: page.location.raw_hex.clone(),
-            hierarchical: make_hierarchical_display(&state.library, &page.location),
-            content: page.content,
-            formatted_content: page.formatted_content,
-        }).into_response()
+        let page = state.library.layout_page(&state.library.generate_page(&location), layout);
+        Json(make_page_response(&state.library, page)).into_response()
     }
 
     async fn get_next_page(
         State(state): State<AppState>,
-        Query(query): Query<AddressQuery>,
+        Query(query): Query<PageQuery>,
     ) -> impl IntoResponse {
         let location = match state.library.parse_address(&query.address) {
             Some(loc) => loc,
@@ -219,19 +582,32 @@
             }
         };
 
-        let page = state.library.next_page(&location);
+        let Some(layout) = query.layout(&state.library) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Layout must be fixed, wrap, justified or verse" })),
+            ).into_response();
+        };
 
-        Json(PageResponse {
-            raw_address: page.location.raw_hex.clone(),
-            hierarchical: make_hierarchical_display(&state.library, &page.location),
-            content: page.content,
-            formatted_content: page.formatted_content,
-        }).into_response()
+        match state.library.next_page(&location) {
+            Some(page) => {
+                let page = state.library.layout_page(&page, layout);
+                Json(make_page_response(&state.library, page)).into_response()
+            }
+            None => {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({
+                        "error": "Already at the last page of the library"
+                    })),
+                ).into_response()
+            }
+        }
     }
 
     async fn get_previous_page(
         State(state): State<AppState>,
-        Query(query): Query<AddressQuery>,
+        Query(query): Query<PageQuery>,
     ) -> impl IntoResponse {
         let location = match state.library.parse_address(&query.address) {
             Some(loc) => loc,
@@ -245,14 +621,17 @@
             }
         };
 
+        let Some(layout) = query.layout(&state.library) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Layout must be fixed, wrap, justified or verse" })),
+            ).into_response();
+        };
+
         match state.library.previous_page(&location) {
             Some(page) => {
-                Json(PageResponse {
-                    raw_address: page.location.raw_hex.clone(),
-                    hierarchical: make_hierarchical_display(&state.library, &page.location),
-                    content: page.content,
-                    formatted_content: page.formatted_content,
-                }).into_response()
+                let page = state.library.layout_page(&page, layout);
+                Json(make_page_response(&state.library, page)).into_response()
             }
             None => {
                 (
@@ -265,95 +644,609 @@
         }
     }
 
+    async fn get_book(
+        State(state): State<AppState>,
+        Query(query): Query<AddressQuery>,
+    ) -> impl IntoResponse {
+        let location = match state.library.parse_address(&query.address) {
+            Some(loc) => loc,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "error": "Invalid address format"
+                    })),
+                ).into_response();
+            }
+        };
+
+        // A whole book is hundreds of pages to generate and lay out
+        let library = Arc::clone(&state.library);
+        let book = run_blocking(move || {
+            let book = library.generate_book(&location);
+            BookResponse {
+                location: make_location_response(&library, &book.location),
+                title: book.title,
+                pages: book.pages
+                    .into_iter()
+                    .map(|page| make_page_response(&library, page))
+                    .collect(),
+            }
+        }).await;
+
+        match book {
+            Ok(book) => Json(book).into_response(),
+            Err(response) => response,
+        }
+    }
+
+    async fn get_shelf(
+        State(state): State<AppState>,
+        Query(query): Query<AddressQuery>,
+    ) -> impl IntoResponse {
+        let location = match state.library.parse_address(&query.address) {
+            Some(loc) => loc,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "error": "Invalid address format"
+                    })),
+                ).into_response();
+            }
+        };
+
+        let library = &state.library;
+        let books = library
+            .shelf(&location)
+            .into_iter()
+            .map(|spine| SpineResponse {
+                pustaka: spine.pustaka,
+                title: spine.title,
+                location: make_location_response(library, &spine.location),
+            })
+            .collect();
+
+        Json(ShelfResponse {
+            gode: location.hierarchical.gode,
+            patti: location.hierarchical.patti,
+            books,
+        }).into_response()
+    }
+
+    async fn get_neighbours(
+        State(state): State<AppState>,
+        Query(query): Query<AddressQuery>,
+    ) -> impl IntoResponse {
+        let location = match state.library.parse_address(&query.address) {
+            Some(loc) => loc,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "error": "Invalid address format"
+                    })),
+                ).into_response();
+            }
+        };
+
+        let library = &state.library;
+        let towards = |direction| make_location_response(library, &library.walk(&location, direction));
+
+        Json(NeighboursResponse {
+            location: make_location_response(library, &location),
+            up: towards(Direction::Up),
+            down: towards(Direction::Down),
+            left: towards(Direction::Left),
+            right: towards(Direction::Right),
+        }).into_response()
+    }
+
+    async fn walk_to_neighbour(
+        State(state): State<AppState>,
+        Query(query): Query<WalkQuery>,
+    ) -> impl IntoResponse {
+        let (location, direction) = match (
+            state.library.parse_address(&query.address),
+            Direction::from_name(&query.direction),
+        ) {
+            (Some(loc), Some(direction)) => (loc, direction),
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({
+                        "error": "Invalid address or direction (use up, down, left or right)"
+                    })),
+                ).into_response();
+            }
+        };
+
+        let page = state.library.generate_page(&state.library.walk(&location, direction));
+
+        Json(make_page_response(&state.library, page)).into_response()
+    }
+
     async fn search_text(
         State(state): State<AppState>,
         Query(query): Query<SearchQuery>,
     ) -> impl IntoResponse {
         if query.q.is_empty() {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(SearchResponse {
-                    query: query.q,
-                    found: false,
-                    location: None,
-                    page_preview: None,
-                }),
-            );
+            return (StatusCode::BAD_REQUEST, Json(make_search_response(&state.library, query.q, None)));
         }
 
-        match state.library.search(&query.q) {
-            Some(result) => {
-                let page = state.library.generate_page(&result.location);
-                let preview: String = page.content.chars().take(80).collect();
+        let result = state.library.search(&query.q);
+        (StatusCode::OK, Json(make_search_response(&state.library, query.q, result)))
+    }
 
-                (
-                    StatusCode::OK,
-                    Json(SearchResponse {
-                        query: query.q,
-                        found: true,
-                        location: Some(LocationResponse {
-                            raw_address: result.location.raw_hex.clone(),
-                            hierarchical: make_hierarchical_display(&state.library, &result.location),
-                        }),
-                        page_preview: Some(preview),
+    async fn search_random_position(
+        State(state): State<AppState>,
+        Query(query): Query<RandomSearchQuery>,
+    ) -> impl IntoResponse {
+        if query.q.is_empty() {
+            return (StatusCode::BAD_REQUEST, Json(make_search_response(&state.library, query.q, None)));
+        }
+
+        let result = match query.seed {
+            Some(seed) => state.library.search_at_random_position_seeded(&query.q, seed),
+            None => state.library.search_at_random_position(&query.q),
+        };
+        (StatusCode::OK, Json(make_search_response(&state.library, query.q, result)))
+    }
+
+    async fn search_at_placement(
+        State(state): State<AppState>,
+        Query(query): Query<SearchAtQuery>,
+    ) -> impl IntoResponse {
+        let (placement, filler) = match (query.placement(), query.filler()) {
+            (Some(placement), Some(filler)) if !query.q.is_empty() => (placement, filler),
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(make_search_response(&state.library, query.q, None)),
+                );
+            }
+        };
+
+        let mut rng = rng_from_seed(query.seed);
+        let result = state.library.search_filled_with(&query.q, placement, filler, &mut rng);
+        (StatusCode::OK, Json(make_search_response(&state.library, query.q, result)))
+    }
+
+    async fn search_pattern(
+        State(state): State<AppState>,
+        Query(query): Query<SearchAtQuery>,
+    ) -> impl IntoResponse {
+        let (placement, filler) = match (query.placement(), query.filler()) {
+            (Some(placement), Some(filler)) => (placement, filler),
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(PatternSearchResponse {
+                        pattern: query.q,
+                        error: Some("Invalid placement or filler".to_string()),
+                        result: make_search_response(&state.library, String::new(), None),
                     }),
-                )
+                );
             }
-            None => (
+        };
+
+        let result = if query.random.unwrap_or(false) {
+            let mut rng = rng_from_seed(query.seed);
+            state.library.search_pattern_random_with(&query.q, placement, filler, &mut rng)
+        } else {
+            state.library.search_pattern(&query.q, placement)
+        };
+
+        match result {
+            Ok(result) => (
                 StatusCode::OK,
-                Json(SearchResponse {
-                    query: query.q,
-                    found: false,
-                    location: None,
-                    page_preview: None,
+                Json(PatternSearchResponse {
+                    pattern: query.q,
+                    error: None,
+                    result: make_search_response(&state.library, result.query.clone(), Some(result)),
+                }),
+            ),
+            Err(error) => (
+                StatusCode::BAD_REQUEST,
+                Json(PatternSearchResponse {
+                    pattern: query.q,
+                    error: Some(error.to_string()),
+                    result: make_search_response(&state.library, String::new(), None),
                 }),
             ),
         }
     }
 
-    async fn search_random_position(
+    async fn search_constrained(
         State(state): State<AppState>,
-        Query(query): Query<SearchQuery>,
+        Query(query): Query<ConstrainedQuery>,
     ) -> impl IntoResponse {
-        if query.q.is_empty() {
+        let mandira = match query.mandira.as_deref() {
+            None => Some(None),
+            Some(name) if LibraryOfBabel::contains_kannada(name) => {
+                state.library.mandira_from_kannada(name).map(Some)
+            }
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16).map(Some),
+        };
+
+        let constraint = mandira.map(|mandira| AddressConstraint {
+            mandira,
+            gode: query.gode,
+            patti: query.patti,
+            pustaka: query.pustaka,
+            puta: query.puta,
+        });
+        let library = Arc::clone(&state.library);
+        let (q, position) = (query.q.clone(), query.position);
+        let outcome = match run_blocking(move || {
+            constraint.and_then(|constraint| library.search_constrained(&constraint, &q, position))
+        })
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(response) => return response,
+        };
+
+        let (status, outcome, result) = match outcome {
+            Some(ConstrainedSearch::Found(result)) => (StatusCode::OK, "found", Some(result)),
+            Some(ConstrainedSearch::Impossible) => (StatusCode::OK, "impossible", None),
+            Some(ConstrainedSearch::BudgetExceeded) => (StatusCode::OK, "budget_exceeded", None),
+            None => (StatusCode::BAD_REQUEST, "invalid", None),
+        };
+        let result = make_search_response(&state.library, query.q, result);
+        (status, Json(ConstrainedResponse { outcome, result })).into_response()
+    }
+
+    async fn search_shortest(
+        State(state): State<AppState>,
+        Query(query): Query<ShortestQuery>,
+    ) -> impl IntoResponse {
+        const MAX_BUDGET: usize = 100;
+
+        // Each position tried is a modular search over numbers of up to 6,000 bits
+        let budget = query.budget.unwrap_or(50).min(MAX_BUDGET);
+        let library = Arc::clone(&state.library);
+        let q = query.q.clone();
+        let result = match run_blocking(move || library.search_shortest(&q, budget)).await {
+            Ok(result) => result,
+            Err(response) => return response,
+        };
+
+        let status = if query.q.is_empty() { StatusCode::BAD_REQUEST } else { StatusCode::OK };
+        (status, Json(make_search_response(&state.library, query.q, result))).into_response()
+    }
+
+    async fn list_occurrences(
+        State(state): State<AppState>,
+        Query(query): Query<OccurrencesQuery>,
+    ) -> impl IntoResponse {
+        const MAX_LIMIT: usize = 100;
+
+        let token = match query.token.as_deref().map(|t| t.parse::<BigUint>()) {
+            None => Some(BigUint::default()),
+            Some(Ok(token)) => Some(token),
+            Some(Err(_)) => None,
+        };
+        let occurrences = token.and_then(|token| state.library.occurrences_from(&query.q, token));
+
+        let Some(mut occurrences) = occurrences else {
             return (
                 StatusCode::BAD_REQUEST,
-                Json(SearchResponse {
-                    query: query.q,
-                    found: false,
-                    location: None,
-                    page_preview: None,
-                }),
+                Json(OccurrencesResponse { query: query.q, occurrences: Vec::new(), next_token: None }),
             );
+        };
+
+        let limit = query.limit.unwrap_or(10).min(MAX_LIMIT);
+        let found: Vec<OccurrenceResponse> = occurrences
+            .by_ref()
+            .take(limit)
+            .map(|occurrence| OccurrenceResponse {
+                token: occurrence.token.to_string(),
+                result: make_search_response(&state.library, query.q.clone(), Some(occurrence.result)),
+            })
+            .collect();
+
+        // Fewer than asked for means the sequence has run out
+        let next_token = (found.len() == limit).then(|| occurrences.next_token().to_string());
+
+        (
+            StatusCode::OK,
+            Json(OccurrencesResponse { query: query.q, occurrences: found, next_token }),
+        )
+    }
+
+    async fn find_coherent_pages(
+        State(state): State<AppState>,
+        Query(query): Query<CoherentQuery>,
+    ) -> impl IntoResponse {
+        const MAX_SAMPLES: usize = 10_000;
+        const MAX_KEEP: usize = 100;
+
+        let Some(model) = state.coherence.clone() else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "error": "No word list loaded; start the server with AKSHARA_WORDLIST=<file>"
+                })),
+            ).into_response();
+        };
+
+        let mut rng = rng_from_seed(query.seed);
+        let samples = query.samples.unwrap_or(1000).min(MAX_SAMPLES);
+        let keep = query.keep.unwrap_or(10).min(MAX_KEEP);
+
+        // Scoring thousands of pages takes seconds
+        let library = Arc::clone(&state.library);
+        let pages = run_blocking(move || {
+            library
+                .find_coherent_pages_with(&model, samples, keep, &mut rng)
+                .into_iter()
+                .map(|scored| CoherentPageResponse {
+                    location: make_location_response(&library, &scored.location),
+                    content: scored.content,
+                    score: scored.score,
+                })
+                .collect()
+        }).await;
+
+        match pages {
+            Ok(pages) => Json(CoherentResponse { samples, pages }).into_response(),
+            Err(response) => response,
         }
+    }
 
-        match state.library.search_at_random_position(&query.q) {
-            Some(result) => {
-                let page = state.library.generate_page(&result.location);
-                let preview: String = page.content.chars().take(80).collect();
+    async fn get_stats(
+        State(state): State<AppState>,
+        Query(query): Query<StatsQuery>,
+    ) -> impl IntoResponse {
+        const MAX_SAMPLE: usize = 1000;
+        const MAX_TOP: usize = 100;
+
+        let top = query.top.unwrap_or(20).min(MAX_TOP);
+
+        // A whole book or a sample of up to a thousand pages takes a while to generate
+        let library = Arc::clone(&state.library);
+        let stats = run_blocking(move || {
+            let pages = match (&query.address, query.scope.as_deref()) {
+                (Some(address), scope) => library.parse_address(address).and_then(|location| {
+                    match scope {
+                        None | Some("page") => Some(vec![library.generate_page(&location)]),
+                        Some("book") => Some(library.generate_book(&location).pages),
+                        Some(_) => None,
+                    }
+                }),
+                (None, None) => {
+                    let mut rng = rng_from_seed(query.seed);
+                    let count = query.sample.unwrap_or(100).clamp(1, MAX_SAMPLE);
+                    Some((0..count).map(|_| library.random_page_with(&mut rng)).collect())
+                }
+                (None, Some(_)) => None,
+            };
+            pages.map(|pages| library.cluster_stats(&pages, top))
+        }).await;
+
+        match stats {
+            Ok(Some(stats)) => Json(stats).into_response(),
+            Ok(None) => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "Give an address (with scope page or book), or no address to sample random pages"
+                })),
+            ).into_response(),
+            Err(response) => response,
+        }
+    }
+
+    async fn get_probability(
+        State(state): State<AppState>,
+        Query(query): Query<SearchQuery>,
+    ) -> impl IntoResponse {
+        match state.library.text_probability(&query.q) {
+            Some(probability) => Json(probability).into_response(),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Query must be non-empty Kannada text" })),
+            ).into_response(),
+        }
+    }
+
+    async fn explain_search(
+        State(state): State<AppState>,
+        Query(query): Query<SearchQuery>,
+    ) -> impl IntoResponse {
+        match state.library.explain_search(&query.q) {
+            Some(explanation) => Json(explanation).into_response(),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Query must be Kannada text that fits on a page" })),
+            ).into_response(),
+        }
+    }
+
+    async fn explain_page(
+        State(state): State<AppState>,
+        Query(query): Query<AddressQuery>,
+    ) -> impl IntoResponse {
+        match state.library.parse_address(&query.address) {
+            Some(location) => Json(state.library.explain_page(&location)).into_response(),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid address format" })),
+            ).into_response(),
+        }
+    }
+
+    async fn compose_page(
+        State(state): State<AppState>,
+        Json(request): Json<ComposeRequest>,
+    ) -> impl IntoResponse {
+        let bad_request = |error: String, reason: Option<ComposeError>| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": error, "reason": reason })),
+            ).into_response()
+        };
+
+        let filler = match request.filler.as_deref() {
+            None => Filler::Blank,
+            Some(name) => match Filler::from_name(name) {
+                Some(filler) => filler,
+                None => return bad_request(format!("Unknown filler: {}", name), None),
+            },
+        };
+
+        let mut fragments = Vec::with_capacity(request.fragments.len());
+        for (index, fragment) in request.fragments.iter().enumerate() {
+            // Only the start is resolved here; the library checks the fragment fits
+            let position = fragment
+                .placement()
+                .and_then(|placement| placement.resolve(0, state.library.geometry()));
+            match position {
+                Some(position) => fragments.push((position, fragment.text.as_str())),
+                None => return bad_request(format!("Invalid placement for fragment {}", index), None),
+            }
+        }
+
+        let mut rng = rng_from_seed(request.seed);
+
+        match state.library.compose_page_with(&fragments, filler, &mut rng) {
+            Ok(location) => {
+                let page = state.library.generate_page(&location);
+                Json(make_page_response(&state.library, page)).into_response()
+            }
+            Err(error) => bad_request(error.to_string(), Some(error)),
+        }
+    }
+
+    async fn encode_bytes(State(state): State<AppState>, body: Bytes) -> impl IntoResponse {
+        match state.library.encode_file(&body) {
+            Some(locations) => Json(EncodeResponse {
+                length: body.len(),
+                bytes_per_page: state.library.byte_capacity(),
+                addresses: locations
+                    .iter()
+                    .map(|location| make_location_response(&state.library, location))
+                    .collect(),
+            }).into_response(),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Pages can't hold any bytes" })),
+            ).into_response(),
+        }
+    }
 
+    async fn decode_bytes(
+        State(state): State<AppState>,
+        Query(query): Query<DecodeQuery>,
+    ) -> impl IntoResponse {
+        let locations: Option<Vec<Location>> = query
+            .addresses
+            .split(',')
+            .map(|address| state.library.parse_address(address.trim()))
+            .collect();
+
+        match locations.and_then(|locations| state.library.decode_file(&locations)) {
+            Some(data) => (
+                [(header::CONTENT_TYPE, "application/octet-stream")],
+                data,
+            ).into_response(),
+            None => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": "Invalid address or page holds no encoded bytes" })),
+            ).into_response(),
+        }
+    }
+
+    async fn get_daily_page(
+        State(state): State<AppState>,
+        Query(query): Query<DailyQuery>,
+    ) -> impl IntoResponse {
+        let (date, cache_control) = match query.date.as_deref() {
+            Some(date) => match CalendarDate::parse(date) {
+                // A given date's page never changes
+                Some(date) => (date, "public, max-age=31536000, immutable".to_string()),
+                None => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({ "error": "Date must be YYYY-MM-DD" })),
+                    ).into_response();
+                }
+            },
+            None => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs() as i64)
+                    .unwrap_or(0);
+                let max_age = CalendarDate::seconds_until_midnight(now, IST_OFFSET_SECONDS);
                 (
-                    StatusCode::OK,
-                    Json(SearchResponse {
-                        query: query.q,
-                        found: true,
-                        location: Some(LocationResponse {
-                            raw_address: result.location.raw_hex.clone(),
-                            hierarchical: make_hierarchical_display(&state.library, &result.location),
-                        }),
-                        page_preview: Some(preview),
-                    }),
+                    CalendarDate::from_unix_seconds(now, IST_OFFSET_SECONDS),
+                    format!("public, max-age={}", max_age),
                 )
             }
-            None => (
-                StatusCode::OK,
-                Json(SearchResponse {
-                    query: query.q,
-                    found: false,
-                    location: None,
-                    page_preview: None,
-                }),
-            ),
+        };
+
+        let page = state.library.page_of_the_day(&date, query.salt.as_deref());
+        (
+            [(header::CACHE_CONTROL, cache_control)],
+            Json(DailyResponse {
+                date: date.to_string(),
+                page: make_page_response(&state.library, page),
+            }),
+        ).into_response()
+    }
+
+    async fn hide_message(
+        State(state): State<AppState>,
+        Json(request): Json<HideRequest>,
+    ) -> impl IntoResponse {
+        // PBKDF2 stretches the passphrase on purpose, so keep it off the async workers
+        let library = Arc::clone(&state.library);
+        let page = run_blocking(move || {
+            let mut rng = rng_from_seed(request.seed);
+            library
+                .hide_message_with(&request.text, &request.passphrase, &mut rng)
+                .map(|location| make_page_response(&library, library.generate_page(&location)))
+        }).await;
+
+        match page {
+            Ok(Some(page)) => Json(page).into_response(),
+            Ok(None) => (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": "Message must be Kannada text of at most message_capacity clusters",
+                    "message_capacity": state.library.message_capacity(),
+                })),
+            ).into_response(),
+            Err(response) => response,
+        }
+    }
+
+    async fn reveal_message(
+        State(state): State<AppState>,
+        Json(request): Json<RevealRequest>,
+    ) -> impl IntoResponse {
+        let location = match state.library.parse_address(&request.address) {
+            Some(loc) => loc,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "error": "Invalid address format" })),
+                ).into_response();
+            }
+        };
+
+        let library = Arc::clone(&state.library);
+        let passphrase = request.passphrase;
+        match run_blocking(move || library.reveal(&location, &passphrase)).await {
+            Ok(Some(text)) => Json(serde_json::json!({
+                "address": request.address,
+                "text": text,
+            })).into_response(),
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": "Wrong passphrase or no message on this page" })),
+            ).into_response(),
+            Err(response) => response,
         }
     }
 
@@ -381,12 +1274,14 @@
             .collect();
 
         let verified = page.content.starts_with(&params.text);
+        let details = state.library.verify_detailed(&location, &params.text);
 
         Json(VerifyResponse {
             verified,
             address: params.address,
             expected_text: params.text,
             actual_start,
+            details,
         }).into_response()
     }
 
@@ -399,7 +1294,21 @@
         println!("Initializing ಅಕ್ಷರ ಮಂಟಪ...");
 
         let library = Arc::new(LibraryOfBabel::new());
-        let state = AppState { library };
+
+        let coherence = match std::env::var("AKSHARA_WORDLIST") {
+            Ok(path) => match CoherenceModel::load(&path, library.alphabet()) {
+                Ok(model) => {
+                    println!("Loaded {} words from {}", model.word_count(), path);
+                    Some(Arc::new(model))
+                }
+                Err(e) => {
+                    eprintln!("Could not read word list {}: {}", path, e);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+        let state = AppState { library, coherence };
  
         let cors = CorsLayer::new()
             .allow_origin(Any)
@@ -410,11 +1319,31 @@
             .route("/", get(health_check))
             .route("/api/info", get(get_library_info))
             .route("/api/random", get(get_random_page))
+            .route("/api/daily", get(get_daily_page))
             .route("/api/page", get(get_page_by_address))
             .route("/api/page-next", get(get_next_page))
             .route("/api/page-previous", get(get_previous_page))
+            .route("/api/book", get(get_book))
+            .route("/api/shelf", get(get_shelf))
+            .route("/api/neighbours", get(get_neighbours))
+            .route("/api/walk", get(walk_to_neighbour))
             .route("/api/search", get(search_text))
             .route("/api/search-random", get(search_random_position))
+            .route("/api/search-at", get(search_at_placement))
+            .route("/api/search-pattern", get(search_pattern))
+            .route("/api/search-shortest", get(search_shortest))
+            .route("/api/search-constrained", get(search_constrained))
+            .route("/api/occurrences", get(list_occurrences))
+            .route("/api/coherent", get(find_coherent_pages))
+            .route("/api/stats", get(get_stats))
+            .route("/api/probability", get(get_probability))
+            .route("/api/explain-search", get(explain_search))
+            .route("/api/explain-page", get(explain_page))
+            .route("/api/compose", post(compose_page))
+            .route("/api/encode", post(encode_bytes))
+            .route("/api/decode", get(decode_bytes))
+            .route("/api/hide", post(hide_message))
+            .route("/api/reveal", post(reveal_message))
             .route("/api/verify", get(verify_text))
             .layer(cors)
             .with_state(state);
@@ -434,11 +1363,31 @@
         println!("║    GET /                      Health check                   ║");
         println!("║    GET /api/info              Library statistics             ║");
         println!("║    GET /api/random            Random page                    ║");
+        println!("║    GET /api/daily             Page of the day (IST)          ║");
         println!("║    GET /api/page              Browse by address              ║");
         println!("║    GET /api/page-next         Next page                      ║");
         println!("║    GET /api/page-previous     Previous page                  ║");
+        println!("║    GET /api/book              Whole book with spine title    ║");
+        println!("║    GET /api/shelf             Spine titles on a shelf        ║");
+        println!("║    GET /api/neighbours        Adjacent rooms                 ║");
+        println!("║    GET /api/walk              Walk to an adjacent room       ║");
         println!("║    GET /api/search            Find text (at start)           ║");
         println!("║    GET /api/search-random     Find text (random position)    ║");
+        println!("║    GET /api/search-at         Find text (chosen position)    ║");
+        println!("║    GET /api/search-pattern    Find text matching a pattern   ║");
+        println!("║    GET /api/search-shortest   Find text (smallest address)   ║");
+        println!("║    GET /api/search-constrained Find text at fixed level      ║");
+        println!("║    GET /api/occurrences       Every page with the text       ║");
+        println!("║    GET /api/coherent          Most Kannada-like random pages ║");
+        println!("║    GET /api/stats             Cluster frequencies, entropy   ║");
+        println!("║    GET /api/probability       Odds of finding text by chance ║");
+        println!("║    GET /api/explain-search    Text to address, step by step  ║");
+        println!("║    GET /api/explain-page      Address to text, step by step  ║");
+        println!("║    POST /api/compose          Page from placed fragments     ║");
+        println!("║    POST /api/encode           Hide a file in the library     ║");
+        println!("║    GET /api/decode            Recover a hidden file          ║");
+        println!("║    POST /api/hide             Encrypt a message onto a page  ║");
+        println!("║    POST /api/reveal           Decrypt a hidden message       ║");
         println!("║    GET /api/verify            Verify bijection               ║");
         println!("╚══════════════════════════════════════════════════════════════╝");
         println!();
@@ -0,0 +1,77 @@
+//! Calendar dates for the page of the day
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// A day in the proleptic Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CalendarDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl CalendarDate {
+    /// None unless `day` exists in `month` of `year`
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let valid = (1..=12).contains(&month) && day >= 1 && day <= Self::days_in_month(year, month);
+        valid.then_some(CalendarDate { year, month, day })
+    }
+
+    /// Parse an ISO `YYYY-MM-DD` date
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.trim().splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok()?;
+        let day = parts.next()?.parse().ok()?;
+        Self::new(year, month, day)
+    }
+
+    fn is_leap_year(year: i32) -> bool {
+        (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+    }
+
+    fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            2 if Self::is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// Date `days` after 1970-01-01 (Howard Hinnant's civil_from_days)
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        CalendarDate { year: year as i32, month, day }
+    }
+
+    /// The local date at a Unix time, in a zone `offset_seconds` east of UTC
+    pub fn from_unix_seconds(seconds: i64, offset_seconds: i64) -> Self {
+        Self::from_days_since_epoch((seconds + offset_seconds).div_euclid(SECONDS_PER_DAY))
+    }
+
+    /// Seconds from a Unix time until the next local midnight (1 to 86400)
+    pub fn seconds_until_midnight(seconds: i64, offset_seconds: i64) -> i64 {
+        SECONDS_PER_DAY - (seconds + offset_seconds).rem_euclid(SECONDS_PER_DAY)
+    }
+}
+
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
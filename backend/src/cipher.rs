@@ -0,0 +1,110 @@
+//! Passphrase-encrypted messages hidden on pages
+//!
+//! A message page starts with a random nonce of [`NONCE_CLUSTERS`] clusters,
+//! stored in the clear. The rest of the page is a plaintext of one length
+//! cluster, [`CHECK_CLUSTERS`] spaces, the message and random padding, with each
+//! cluster shifted (mod N) by a keystream. The keystream comes from ChaCha20
+//! keyed by PBKDF2-HMAC-SHA256 of the passphrase, salted with the nonce. Without
+//! the passphrase every cluster after the nonce is uniformly random, so the page
+//! looks like any other; with the wrong one the check clusters come out as
+//! noise and nothing is revealed.
+
+use pbkdf2::pbkdf2_hmac;
+use rand::{Rng, SeedableRng};
+use sha2::Sha256;
+
+use crate::rng::{random_index, LibraryRng};
+
+/// Clusters of clear random nonce at the start of a message page
+pub const NONCE_CLUSTERS: usize = 8;
+
+/// Known clusters that must decrypt to spaces for a passphrase to be accepted
+pub const CHECK_CLUSTERS: usize = 4;
+
+const KDF_ROUNDS: u32 = 100_000;
+
+/// Clusters before the message: nonce, length and check
+const HEADER_CLUSTERS: usize = NONCE_CLUSTERS + 1 + CHECK_CLUSTERS;
+
+/// Keystream for one passphrase and nonce
+pub struct MessageKey {
+    stream: LibraryRng,
+    alphabet_size: usize,
+}
+
+impl MessageKey {
+    pub fn derive(passphrase: &str, nonce: &[usize], alphabet_size: usize) -> Self {
+        let salt: Vec<u8> = nonce.iter().flat_map(|&i| (i as u32).to_be_bytes()).collect();
+        let mut seed = [0u8; 32];
+        pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, KDF_ROUNDS, &mut seed);
+
+        MessageKey { stream: LibraryRng::from_seed(seed), alphabet_size }
+    }
+
+    fn next_shift(&mut self) -> usize {
+        random_index(&mut self.stream, self.alphabet_size)
+    }
+
+    pub fn encrypt(&mut self, plain: &[usize]) -> Vec<usize> {
+        plain
+            .iter()
+            .map(|&p| (p + self.next_shift()) % self.alphabet_size)
+            .collect()
+    }
+
+    pub fn decrypt(&mut self, cipher: &[usize]) -> Vec<usize> {
+        cipher
+            .iter()
+            .map(|&c| (c + self.alphabet_size - self.next_shift()) % self.alphabet_size)
+            .collect()
+    }
+}
+
+/// Longest message (in clusters) a page of `page_length` clusters can hold
+pub fn message_capacity(page_length: usize, alphabet_size: usize) -> usize {
+    page_length.saturating_sub(HEADER_CLUSTERS).min(alphabet_size - 1)
+}
+
+/// Page clusters hiding `message`, with nonce and padding drawn from `rng`
+pub fn seal<R: Rng + ?Sized>(
+    message: &[usize],
+    passphrase: &str,
+    page_length: usize,
+    alphabet_size: usize,
+    rng: &mut R,
+) -> Option<Vec<usize>> {
+    if page_length < HEADER_CLUSTERS || message.len() > message_capacity(page_length, alphabet_size) {
+        return None;
+    }
+    let mut random_cluster = || random_index(rng, alphabet_size);
+
+    let nonce: Vec<usize> = (0..NONCE_CLUSTERS).map(|_| random_cluster()).collect();
+
+    let mut plain = Vec::with_capacity(page_length - NONCE_CLUSTERS);
+    plain.push(message.len());
+    plain.extend(std::iter::repeat_n(0, CHECK_CLUSTERS));
+    plain.extend_from_slice(message);
+    while plain.len() < page_length - NONCE_CLUSTERS {
+        plain.push(random_cluster());
+    }
+
+    let mut page = nonce.clone();
+    page.extend(MessageKey::derive(passphrase, &nonce, alphabet_size).encrypt(&plain));
+    Some(page)
+}
+
+/// Message clusters hidden on `page`, or None for the wrong passphrase
+pub fn open(page: &[usize], passphrase: &str, alphabet_size: usize) -> Option<Vec<usize>> {
+    if page.len() < HEADER_CLUSTERS {
+        return None;
+    }
+    let (nonce, cipher) = page.split_at(NONCE_CLUSTERS);
+    let plain = MessageKey::derive(passphrase, nonce, alphabet_size).decrypt(cipher);
+
+    let (&length, rest) = plain.split_first()?;
+    let (check, body) = rest.split_at(CHECK_CLUSTERS);
+    if check.iter().any(|&c| c != 0) {
+        return None;
+    }
+    body.get(..length).map(<[usize]>::to_vec)
+}
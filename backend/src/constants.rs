@@ -16,4 +16,17 @@ pub const BOOKS_PER_SHELF: u32 = 32;
 pub const SHELVES_PER_WALL: u32 = 5;
 
 /// Walls per room (ಮಂದಿರ)
-pub const WALLS_PER_ROOM: u32 = 4;
\ No newline at end of file
+pub const WALLS_PER_ROOM: u32 = 4;
+
+/// Clusters in a book's spine title
+pub const SPINE_TITLE_CLUSTERS: usize = 8;
+
+/// Bytes of length header on a page holding encoded data
+pub const BYTE_LENGTH_HEADER: usize = 2;
+
+/// India Standard Time (UTC+05:30), when the page of the day changes
+pub const IST_OFFSET_SECONDS: i64 = 5 * 3600 + 30 * 60;
+
+/// Work a constrained search may do before giving up, counted as the digits
+/// of every modular problem it solves
+pub const MAX_CONSTRAINED_WORK: u64 = 1 << 18;
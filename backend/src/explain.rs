@@ -0,0 +1,83 @@
+//! Step-by-step walkthroughs of the bijection
+//!
+//! Every number is a decimal string, as in [`BijectionProof`](crate::BijectionProof),
+//! so a frontend can show (and check) the real values at each step.
+
+use serde::Serialize;
+
+/// One cluster of a text, with its alphabet index (its digit in base N)
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedCluster {
+    pub position: usize,
+    pub text: String,
+    pub index: usize,
+}
+
+/// `operand × factor = product = quotient × modulus + remainder`
+#[derive(Debug, Clone, Serialize)]
+pub struct ModularStep {
+    pub operand: String,
+    pub factor: String,
+    pub product: String,
+    pub modulus: String,
+    pub quotient: String,
+    pub remainder: String,
+}
+
+/// One level of the room hierarchy: `dividend = quotient × divisor + remainder`
+///
+/// The level's 1-based number is `remainder + 1`, and the quotient is the
+/// dividend of the next level up; the last quotient is the mandira.
+#[derive(Debug, Clone, Serialize)]
+pub struct HierarchyStep {
+    pub level: &'static str,
+    pub dividend: String,
+    pub divisor: u32,
+    pub quotient: String,
+    pub remainder: u32,
+    pub value: u32,
+}
+
+/// How the raw address splits into mandira, gode, patti, pustaka and puta
+#[derive(Debug, Clone, Serialize)]
+pub struct HierarchySplit {
+    pub raw_address: String,
+    pub raw_hex: String,
+    /// From puta (the page) up to gode (the wall)
+    pub steps: Vec<HierarchyStep>,
+    pub mandira: String,
+    pub mandira_hex: String,
+    pub hierarchical: String,
+}
+
+/// From text to address: clusters, digits, content number, multiplication
+/// and reduction, then the hierarchical split
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchExplanation {
+    pub query: String,
+    pub alphabet_size: usize,
+    pub page_length: usize,
+    pub clusters: Vec<ExplainedCluster>,
+    /// The query's indices padded with spaces (0) to a full page, most significant first
+    pub digits: Vec<usize>,
+    pub content_num: String,
+    /// content_num × multiplier mod N^page_length
+    pub multiplication: ModularStep,
+    pub address: HierarchySplit,
+}
+
+/// From address to text: the hierarchical split, multiplication by the
+/// inverse and reduction, then the digits read off as clusters
+#[derive(Debug, Clone, Serialize)]
+pub struct PageExplanation {
+    pub alphabet_size: usize,
+    pub page_length: usize,
+    pub address: HierarchySplit,
+    /// raw address × inverse mod N^page_length
+    pub multiplication: ModularStep,
+    pub content_num: String,
+    /// content_num in base N, most significant first
+    pub digits: Vec<usize>,
+    pub clusters: Vec<ExplainedCluster>,
+    pub content: String,
+}
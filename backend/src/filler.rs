@@ -0,0 +1,203 @@
+//! Filler: what goes on a page around the text that was searched for
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::{GraphemeAlphabet, KannadaScript};
+use crate::rng::random_index;
+
+/// How to fill the clusters of a page that aren't fixed by a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filler {
+    /// Spaces (cluster index 0)
+    #[default]
+    Blank,
+    /// Every cluster equally likely
+    Uniform,
+    /// Clusters drawn with approximate Kannada prose frequencies
+    Kannada,
+    /// The query repeated, in step with where it was placed
+    RepeatQuery,
+}
+
+impl Filler {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blank" => Some(Filler::Blank),
+            "uniform" => Some(Filler::Uniform),
+            "kannada" => Some(Filler::Kannada),
+            "repeat" | "repeat_query" => Some(Filler::RepeatQuery),
+            _ => None,
+        }
+    }
+
+    /// Build a page of `length` clusters with `query` at `position`
+    ///
+    /// Random fillers draw one cluster per free slot, in page order.
+    pub fn fill<R: Rng + ?Sized>(
+        &self,
+        query: &[usize],
+        position: usize,
+        length: usize,
+        weights: &ClusterWeights,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        self.compose(&[(position, query)], length, weights, rng)
+    }
+
+    /// Build a page of `length` clusters with each fragment at its position
+    ///
+    /// Fragments must not overlap. [`Filler::RepeatQuery`] repeats the first fragment.
+    pub fn compose<R: Rng + ?Sized>(
+        &self,
+        fragments: &[(usize, &[usize])],
+        length: usize,
+        weights: &ClusterWeights,
+        rng: &mut R,
+    ) -> Vec<usize> {
+        let fixed = |i: usize| {
+            fragments.iter().find_map(|&(position, text)| {
+                i.checked_sub(position).and_then(|offset| text.get(offset).copied())
+            })
+        };
+
+        (0..length)
+            .map(|i| {
+                if let Some(cluster) = fixed(i) {
+                    return cluster;
+                }
+                match (self, fragments.first()) {
+                    (Filler::Blank, _) => 0,
+                    (Filler::Uniform, _) => random_index(rng, weights.len()),
+                    (Filler::Kannada, _) => weights.sample(rng),
+                    (Filler::RepeatQuery, Some(&(position, query))) if !query.is_empty() => {
+                        query[(i + query.len() - position % query.len()) % query.len()]
+                    }
+                    (Filler::RepeatQuery, _) => 0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Approximate relative frequencies of Kannada letters in prose, per mille
+///
+/// These are rough figures, good enough to make filler read like Kannada
+/// rather than a uniform soup of rare conjuncts.
+const CONSONANT_WEIGHTS: [(char, u64); 36] = [
+    ('ಕ', 70), ('ಖ', 3), ('ಗ', 40), ('ಘ', 2), ('ಙ', 1),
+    ('ಚ', 15), ('ಛ', 1), ('ಜ', 15), ('ಝ', 1), ('ಞ', 1),
+    ('ಟ', 20), ('ಠ', 2), ('ಡ', 30), ('ಢ', 1), ('ಣ', 15),
+    ('ತ', 60), ('ಥ', 3), ('ದ', 60), ('ಧ', 8), ('ನ', 90),
+    ('ಪ', 35), ('ಫ', 2), ('ಬ', 30), ('ಭ', 8), ('ಮ', 50),
+    ('ಯ', 40), ('ರ', 80), ('ಱ', 1), ('ಲ', 70), ('ಳ', 25), ('ೞ', 1), ('ವ', 50),
+    ('ಶ', 12), ('ಷ', 8), ('ಸ', 45), ('ಹ', 30),
+];
+
+const VOWEL_WEIGHTS: [(char, u64); 14] = [
+    ('ಅ', 40), ('ಆ', 25), ('ಇ', 25), ('ಈ', 8), ('ಉ', 20), ('ಊ', 5), ('ಋ', 1),
+    ('ೠ', 1), ('ಎ', 15), ('ಏ', 8), ('ಐ', 3), ('ಒ', 10), ('ಓ', 5), ('ಔ', 1),
+];
+
+/// Vowel signs, relative to the inherent vowel at 100
+const MATRA_WEIGHTS: [(char, u64); 13] = [
+    ('ಾ', 60), ('ಿ', 70), ('ೀ', 15), ('ು', 80), ('ೂ', 15), ('ೃ', 5), ('ೄ', 1),
+    ('ೆ', 45), ('ೇ', 20), ('ೈ', 8), ('ೊ', 15), ('ೋ', 12), ('ೌ', 2),
+];
+
+const INHERENT_VOWEL_WEIGHT: u64 = 100;
+
+/// Factor for a cluster with no modifier, anusvara (ಂ) or visarga (ಃ)
+const NO_MODIFIER: u64 = 1000;
+const ANUSVARA: u64 = 80;
+const VISARGA: u64 = 2;
+
+const SPACE_WEIGHT: u64 = 120_000_000;
+const PUNCTUATION_WEIGHTS: [(char, u64); 5] = [
+    ('.', 6_000_000), (',', 4_000_000), ('।', 2_000_000), ('!', 400_000), ('?', 400_000),
+];
+
+/// Cumulative cluster weights for sampling filler
+///
+/// Weights are integers so seeded sampling gives the same clusters on every platform.
+pub struct ClusterWeights {
+    cumulative: Vec<u64>,
+}
+
+impl ClusterWeights {
+    /// Approximate Kannada prose frequencies for every cluster of `alphabet`
+    pub fn kannada(alphabet: &GraphemeAlphabet) -> Self {
+        let mut total = 0u64;
+        let cumulative = (0..alphabet.size())
+            .map(|i| {
+                total += Self::weight(alphabet.get(i).unwrap_or("")).max(1);
+                total
+            })
+            .collect();
+        ClusterWeights { cumulative }
+    }
+
+    fn weight(cluster: &str) -> u64 {
+        let lookup = |table: &[(char, u64)], c: char| {
+            table.iter().find(|(t, _)| *t == c).map(|(_, w)| *w)
+        };
+        let chars: Vec<char> = cluster.chars().collect();
+        let Some(&first) = chars.first() else { return 0 };
+
+        if first == ' ' {
+            return SPACE_WEIGHT;
+        }
+        if let Some(w) = lookup(&PUNCTUATION_WEIGHTS, first) {
+            return w;
+        }
+
+        let (modifier, body) = match chars.last() {
+            Some('ಂ') => (ANUSVARA, &chars[..chars.len() - 1]),
+            Some('ಃ') => (VISARGA, &chars[..chars.len() - 1]),
+            _ => (NO_MODIFIER, &chars[..]),
+        };
+
+        // Independent vowels mostly start words, so they are scaled down
+        if let Some(w) = lookup(&VOWEL_WEIGHTS, first) {
+            return w * modifier * 200;
+        }
+
+        let halant = KannadaScript::halant();
+        let consonant = |c: char| lookup(&CONSONANT_WEIGHTS, c).unwrap_or(1);
+        let vowel_sign = |rest: &[char]| match rest {
+            [] => INHERENT_VOWEL_WEIGHT,
+            [m] => lookup(&MATRA_WEIGHTS, *m).unwrap_or(1),
+            _ => 1,
+        };
+
+        match body {
+            // Dead consonant (ನ್) and dead conjunct (ರ್ನ್)
+            [c, h] if *h == halant => consonant(*c) * INHERENT_VOWEL_WEIGHT * modifier / 20,
+            [c1, h1, c2, h2] if *h1 == halant && *h2 == halant => {
+                consonant(*c1) * consonant(*c2) * modifier / 20
+            }
+            // Conjunct, with geminates (ಕ್ಕ, ನ್ನ, ಲ್ಲ) much more common than others
+            [c1, h, c2, rest @ ..] if *h == halant => {
+                let geminate = if c1 == c2 { 6 } else { 1 };
+                consonant(*c1) * consonant(*c2) * vowel_sign(rest) * modifier * geminate / 6000
+            }
+            [c, rest @ ..] => consonant(*c) * vowel_sign(rest) * modifier,
+            [] => 0,
+        }
+    }
+
+    pub fn len(&self) -> usize { self.cumulative.len() }
+
+    pub fn is_empty(&self) -> bool { self.cumulative.is_empty() }
+
+    /// Draw one cluster index
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let total = self.cumulative.last().copied().unwrap_or(0);
+        if total == 0 {
+            return 0;
+        }
+        let target = rng.gen_range(0..total);
+        self.cumulative.partition_point(|&c| c <= target)
+    }
+}
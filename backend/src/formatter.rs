@@ -0,0 +1,151 @@
+//! Line layouts for displaying a page
+
+use serde::{Deserialize, Serialize};
+
+use crate::alphabet::GraphemeAlphabet;
+use crate::types::LineSpan;
+
+const SPACE: usize = 0;
+
+/// How a page's clusters are broken into lines
+///
+/// Widths are in clusters. Every layout keeps every cluster, in order; only
+/// where the lines break (and, for [`Layout::Justified`], the spacing) differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Layout {
+    /// Hard breaks every `clusters_per_line` clusters, mid-word if need be
+    #[default]
+    Fixed,
+    /// Break at spaces so no line is wider than `width`; longer words are split
+    WordWrap { width: usize },
+    /// As [`Layout::WordWrap`], with spaces widened so every full line is `width` wide
+    Justified { width: usize },
+    /// A line break after every `।`, with longer verses word-wrapped at `width`
+    Verse { width: usize },
+}
+
+impl Layout {
+    /// Layout by name (fixed, wrap, justified or verse), with `width` for the wrapping ones
+    pub fn from_name(name: &str, width: usize) -> Option<Self> {
+        match name {
+            "fixed" => Some(Layout::Fixed),
+            "wrap" | "word_wrap" => Some(Layout::WordWrap { width }),
+            "justified" => Some(Layout::Justified { width }),
+            "verse" => Some(Layout::Verse { width }),
+            _ => None,
+        }
+    }
+
+    /// Formatted text and its lines, for a page of `indices`
+    ///
+    /// `line_width` is the library's `clusters_per_line`, used by [`Layout::Fixed`].
+    pub fn format(
+        &self,
+        indices: &[usize],
+        alphabet: &GraphemeAlphabet,
+        line_width: usize,
+    ) -> (String, Vec<LineSpan>) {
+        let danda = alphabet.index_of("।");
+        let breaks = match *self {
+            Layout::Fixed => Self::fixed_breaks(indices.len(), line_width),
+            Layout::WordWrap { width } | Layout::Justified { width } => {
+                Self::wrap_breaks(indices, width, None)
+            }
+            Layout::Verse { width } => Self::wrap_breaks(indices, width, danda),
+        };
+
+        let mut text = String::new();
+        let mut lines = Vec::with_capacity(breaks.len());
+        for (line, &(start, end)) in breaks.iter().enumerate() {
+            if line > 0 {
+                text.push('\n');
+            }
+            let char_start = text.chars().count();
+
+            let visible = match self {
+                Layout::Fixed => &indices[start..end],
+                _ => Self::trim_spaces(&indices[start..end]),
+            };
+            let last_line = line + 1 == breaks.len();
+            match self {
+                Layout::Justified { width } if !last_line => {
+                    Self::push_justified(&mut text, visible, *width, alphabet)
+                }
+                _ => text.push_str(&alphabet.indices_to_string(visible)),
+            }
+
+            lines.push(LineSpan { start, end, char_start, char_end: text.chars().count() });
+        }
+        (text, lines)
+    }
+
+    fn fixed_breaks(length: usize, width: usize) -> Vec<(usize, usize)> {
+        let width = width.max(1);
+        (0..length)
+            .step_by(width)
+            .map(|start| (start, (start + width).min(length)))
+            .collect()
+    }
+
+    /// Greedy line breaks at spaces, with the spaces at a break hanging at the
+    /// end of the line; a line also ends after every `forced` cluster
+    fn wrap_breaks(indices: &[usize], width: usize, forced: Option<usize>) -> Vec<(usize, usize)> {
+        let width = width.max(1);
+        let length = indices.len();
+        let hang = |mut end: usize| {
+            while end < length && indices[end] == SPACE {
+                end += 1;
+            }
+            end
+        };
+
+        let mut breaks = Vec::new();
+        let mut start = 0;
+        while start < length {
+            let limit = (start + width).min(length);
+            let forced_end = forced.and_then(|forced| {
+                indices[start..limit].iter().position(|&i| i == forced).map(|at| start + at + 1)
+            });
+
+            let end = if let Some(end) = forced_end {
+                hang(end)
+            } else if limit == length || indices[limit] == SPACE {
+                hang(limit)
+            } else {
+                // Break at the last space on the line, or mid-word if there is none
+                match indices[start + 1..limit].iter().rposition(|&i| i == SPACE) {
+                    Some(at) => hang(start + 1 + at),
+                    None => limit,
+                }
+            };
+            breaks.push((start, end));
+            start = end;
+        }
+        breaks
+    }
+
+    fn trim_spaces(line: &[usize]) -> &[usize] {
+        let visible = line.iter().rposition(|&i| i != SPACE).map_or(0, |last| last + 1);
+        &line[..visible]
+    }
+
+    /// Widen the spaces between words, leftmost gaps first, to fill `width`
+    fn push_justified(text: &mut String, line: &[usize], width: usize, alphabet: &GraphemeAlphabet) {
+        let gaps = line
+            .windows(2)
+            .filter(|pair| pair[0] != SPACE && pair[1] == SPACE)
+            .count();
+        let extra = width.saturating_sub(line.len());
+
+        let mut gap = 0;
+        for (i, &index) in line.iter().enumerate() {
+            text.push_str(alphabet.get(index).unwrap_or(""));
+            if gaps > 0 && index != SPACE && line.get(i + 1) == Some(&SPACE) {
+                let padding = extra / gaps + usize::from(gap < extra % gaps);
+                text.extend(std::iter::repeat_n(' ', padding));
+                gap += 1;
+            }
+        }
+    }
+}
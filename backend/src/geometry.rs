@@ -0,0 +1,74 @@
+//! Library geometry: page size and the shape of the room hierarchy
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::*;
+
+/// Shape of a library
+///
+/// The defaults are the Borges-faithful constants. Smaller geometries such as
+/// [`LibraryGeometry::pamphlet`] keep the same structure with far fewer pages,
+/// which is handy for teaching and tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryGeometry {
+    pub clusters_per_page: usize,
+    pub clusters_per_line: usize,
+    pub pages_per_book: u32,
+    pub books_per_shelf: u32,
+    pub shelves_per_wall: u32,
+    pub walls_per_room: u32,
+}
+
+impl LibraryGeometry {
+    /// The library as described by Borges
+    pub const fn borges() -> Self {
+        LibraryGeometry {
+            clusters_per_page: CLUSTERS_PER_PAGE,
+            clusters_per_line: CLUSTERS_PER_LINE,
+            pages_per_book: PAGES_PER_BOOK,
+            books_per_shelf: BOOKS_PER_SHELF,
+            shelves_per_wall: SHELVES_PER_WALL,
+            walls_per_room: WALLS_PER_ROOM,
+        }
+    }
+
+    /// A small library of 40-cluster pages
+    pub const fn pamphlet() -> Self {
+        LibraryGeometry {
+            clusters_per_page: 40,
+            clusters_per_line: 10,
+            ..Self::borges()
+        }
+    }
+
+    /// Every dimension must be non-zero
+    pub fn is_valid(&self) -> bool {
+        self.clusters_per_page > 0
+            && self.clusters_per_line > 0
+            && self.pages_per_book > 0
+            && self.books_per_shelf > 0
+            && self.shelves_per_wall > 0
+            && self.walls_per_room > 0
+    }
+
+    pub fn pages_per_shelf(&self) -> u64 {
+        self.pages_per_book as u64 * self.books_per_shelf as u64
+    }
+
+    pub fn pages_per_wall(&self) -> u64 {
+        self.pages_per_shelf() * self.shelves_per_wall as u64
+    }
+
+    pub fn pages_per_room(&self) -> u64 {
+        self.pages_per_wall() * self.walls_per_room as u64
+    }
+
+    /// Number of lines on a page (the last may be short)
+    pub fn lines_per_page(&self) -> usize {
+        self.clusters_per_page.div_ceil(self.clusters_per_line)
+    }
+}
+
+impl Default for LibraryGeometry {
+    fn default() -> Self { Self::borges() }
+}
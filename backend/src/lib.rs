@@ -6,16 +6,49 @@
 //! and every address maps to exactly one page.
 
 mod constants;
+mod geometry;
 mod alphabet;
 mod bijection;
 mod types;
+mod mandira;
+mod topology;
+mod rng;
+mod filler;
+mod formatter;
+mod pattern;
+mod cipher;
+mod calendar;
+mod scoring;
+mod analysis;
+mod probability;
+mod explain;
 mod library;
 
 pub use constants::*;
-pub use alphabet::{GraphemeAlphabet, KannadaScript};
+pub use geometry::LibraryGeometry;
+pub use alphabet::{ClusterKind, GraphemeAlphabet, KannadaScript};
 pub use bijection::BijectionEngine;
-pub use types::{HierarchicalAddress, Location, Page, SearchResult};
-pub use library::LibraryOfBabel;
+pub use types::{
+    AddressConstraint, AddressDistance, BijectionProof, Book, BookSpine, ClusterComparison,
+    ComposeError, ConstrainedSearch, HierarchicalAddress, Level, LineSpan, Location, MatchSpan,
+    Page, Placement, SearchResult, Verification, VerifyDiff,
+};
+pub use mandira::{Mandira, MANDIRA_SEPARATOR};
+pub use topology::{Direction, GalleryTopology, Neighbours};
+pub use rng::{entropy_rng, random_below, random_index, rng_from_seed, seeded_rng, LibraryRng};
+pub use filler::{ClusterWeights, Filler};
+pub use formatter::Layout;
+pub use pattern::{Pattern, PatternError, MAX_PATTERN_CHARS, MAX_PATTERN_DEPTH};
+pub use cipher::{CHECK_CLUSTERS, NONCE_CLUSTERS};
+pub use calendar::CalendarDate;
+pub use scoring::{CoherenceModel, PageScore, ScoredPage, WordMatch, MIN_WORD_CLUSTERS};
+pub use analysis::{ClusterCount, ClusterStats, KindCount, Run};
+pub use probability::TextProbability;
+pub use explain::{
+    ExplainedCluster, HierarchySplit, HierarchyStep, ModularStep, PageExplanation,
+    SearchExplanation,
+};
+pub use library::{LibraryOfBabel, Occurrence, Occurrences};
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
@@ -1,99 +1,589 @@
 //! Main library API
 
 use num_bigint::BigUint;
-use num_traits::Zero;
-use std::collections::hash_map::RandomState;
-use std::hash::{Hash, Hasher, BuildHasher};
-
-#[cfg(not(feature = "wasm"))]
-use std::time::{SystemTime, UNIX_EPOCH};
-
-/// Get current timestamp in nanoseconds (WASM-compatible)
-fn get_timestamp_nanos() -> u128 {
-    #[cfg(feature = "wasm")]
-    {
-        // Use JavaScript's Date.now() which returns milliseconds
-        let millis = js_sys::Date::now() as u128;
-        millis * 1_000_000 // Convert to nanoseconds
-    }
-
-    #[cfg(not(feature = "wasm"))]
-    {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos()
-    }
-}
+use num_integer::Integer;
+use num_traits::{ToPrimitive, Zero};
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
 
 use crate::alphabet::GraphemeAlphabet;
+use crate::analysis::ClusterStats;
 use crate::bijection::BijectionEngine;
-use crate::constants::{CLUSTERS_PER_PAGE, CLUSTERS_PER_LINE};
-use crate::types::{HierarchicalAddress, Location, Page, SearchResult};
+use crate::calendar::CalendarDate;
+use crate::cipher;
+use crate::constants::{BYTE_LENGTH_HEADER, MAX_CONSTRAINED_WORK, SPINE_TITLE_CLUSTERS};
+use crate::explain::{
+    ExplainedCluster, HierarchySplit, HierarchyStep, ModularStep, PageExplanation,
+    SearchExplanation,
+};
+use crate::filler::{ClusterWeights, Filler};
+use crate::formatter::Layout;
+use crate::geometry::LibraryGeometry;
+use crate::mandira::Mandira;
+use crate::pattern::{Pattern, PatternError};
+use crate::probability::TextProbability;
+use crate::rng::{entropy_rng, random_below, random_index, seeded_rng, LibraryRng};
+use crate::scoring::{CoherenceModel, PageScore, ScoredPage};
+use crate::topology::{Direction, GalleryTopology, Neighbours};
+use crate::types::{
+    AddressConstraint, BijectionProof, Book, BookSpine, ClusterComparison, ComposeError,
+    ConstrainedSearch, HierarchicalAddress, Level, Location, MatchSpan, Page, Placement,
+    SearchResult, Verification, VerifyDiff,
+};
+
+/// A constrained search used up [`MAX_CONSTRAINED_WORK`]
+struct OutOfWork;
 
 /// Akshara Mantapa main library
 pub struct LibraryOfBabel {
     alphabet: GraphemeAlphabet,
     bijection: BijectionEngine,
+    geometry: LibraryGeometry,
+    topology: GalleryTopology,
+    weights: ClusterWeights,
 }
 
 impl LibraryOfBabel {
     pub fn new() -> Self {
+        Self::with_geometry(LibraryGeometry::default())
+    }
+
+    /// Build a library with a custom geometry
+    pub fn with_geometry(geometry: LibraryGeometry) -> Self {
+        assert!(geometry.is_valid(), "Library geometry dimensions must be non-zero");
+
         let alphabet = GraphemeAlphabet::new();
-        let bijection = BijectionEngine::new(alphabet.size());
-        LibraryOfBabel { alphabet, bijection }
+        let bijection = BijectionEngine::new(alphabet.size(), &geometry);
+
+        let pages_per_room = BigUint::from(geometry.pages_per_room());
+        let topology = GalleryTopology::new(bijection.modulus().div_ceil(&pages_per_room));
+
+        let weights = ClusterWeights::kannada(&alphabet);
+
+        LibraryOfBabel { alphabet, bijection, geometry, topology, weights }
     }
 
     pub fn alphabet_size(&self) -> usize { self.alphabet.size() }
-    
-    pub fn page_length(&self) -> usize { CLUSTERS_PER_PAGE }
+
+    pub fn page_length(&self) -> usize { self.geometry.clusters_per_page }
+
+    pub fn geometry(&self) -> &LibraryGeometry { &self.geometry }
+
+    pub fn alphabet(&self) -> &GraphemeAlphabet { &self.alphabet }
 
     /// Generate a page from a location
     pub fn generate_page(&self, location: &Location) -> Page {
         let raw_address = location.to_raw();
         let content_num = self.bijection.address_to_content(&raw_address);
-        let indices = self.bijection.biguint_to_indices(content_num, CLUSTERS_PER_PAGE);
+        self.page_from_content(location, content_num)
+    }
+
+    fn page_from_content(&self, location: &Location, content_num: BigUint) -> Page {
+        let indices = self.bijection.biguint_to_indices(content_num, self.geometry.clusters_per_page);
         let content = self.alphabet.indices_to_string(&indices);
-        let formatted = self.format_content(&indices);
+        let (formatted, lines) =
+            Layout::Fixed.format(&indices, &self.alphabet, self.geometry.clusters_per_line);
 
         Page {
             location: location.clone(),
             content,
             formatted_content: formatted,
             cluster_indices: indices,
+            lines,
         }
     }
 
+    /// The page laid out in lines by `layout` instead of the fixed width
+    pub fn layout_page(&self, page: &Page, layout: Layout) -> Page {
+        let (formatted, lines) =
+            layout.format(&page.cluster_indices, &self.alphabet, self.geometry.clusters_per_line);
+
+        Page {
+            formatted_content: formatted,
+            lines,
+            ..page.clone()
+        }
+    }
+
+    /// Generate every page of the book containing `location`
+    ///
+    /// Consecutive addresses differ in content by the modular inverse, so only
+    /// the first page needs a multiplication; the rest are one addition each.
+    /// The same fixed step means the first page decides every page after it,
+    /// so text can't be chosen for consecutive pages.
+    /// The last book of the library stops at the last page.
+    pub fn generate_book(&self, location: &Location) -> Book {
+        let first = self.book_start(location);
+        let step = self.bijection.inverse();
+        let modulus = self.bijection.modulus();
+
+        let mut content_num = self.bijection.address_to_content(&first.to_raw());
+        let mut page_location = first.clone();
+        let mut pages = Vec::with_capacity(self.geometry.pages_per_book as usize);
+
+        for _ in 0..self.geometry.pages_per_book {
+            let next_content = (&content_num + step) % modulus;
+            let next_location = page_location.next(modulus, &self.geometry);
+            pages.push(self.page_from_content(&page_location, content_num));
+            let Some(next_location) = next_location else { break };
+            content_num = next_content;
+            page_location = next_location;
+        }
+
+        Book {
+            title: self.spine_title(&first),
+            location: first,
+            pages,
+        }
+    }
+
+    /// First page of the book containing `location`
+    pub fn book_start(&self, location: &Location) -> Location {
+        let mut h = location.hierarchical.clone();
+        h.puta = 1;
+        Location::from_raw_address(&h.to_raw(&self.geometry), &self.geometry)
+    }
+
+    /// Title on the spine of the book containing `location`
+    ///
+    /// The book's number is scrambled by the bijection multiplier modulo
+    /// N^SPINE_TITLE_CLUSTERS. Neighbouring books' titles look unrelated but
+    /// aren't independent: consecutive titles differ by the multiplier.
+    pub fn spine_title(&self, location: &Location) -> String {
+        let book_number = location.to_raw() / self.geometry.pages_per_book;
+        let title_space = self.bijection.alphabet_size().pow(SPINE_TITLE_CLUSTERS as u32);
+        let title_num = (book_number * self.bijection.multiplier()) % title_space;
+
+        let indices = self.bijection.biguint_to_indices(title_num, SPINE_TITLE_CLUSTERS);
+        self.alphabet.indices_to_string(&indices).trim().to_string()
+    }
+
+    /// Spines of every book on the shelf containing `location`
+    pub fn shelf(&self, location: &Location) -> Vec<BookSpine> {
+        (1..=self.geometry.books_per_shelf)
+            .map(|pustaka| {
+                let mut h = location.hierarchical.clone();
+                h.pustaka = pustaka;
+                h.puta = 1;
+                let book = Location::from_raw_address(&h.to_raw(&self.geometry), &self.geometry);
+                BookSpine {
+                    pustaka,
+                    title: self.spine_title(&book),
+                    location: book,
+                }
+            })
+            .collect()
+    }
+
     /// Generate page from raw hex address
     pub fn generate_page_from_hex(&self, hex: &str) -> Option<Page> {
-        let location = Location::from_hex(hex)?;
+        let location = Location::from_hex(hex, &self.geometry)?;
         Some(self.generate_page(&location))
     }
 
     /// Generate page from hierarchical address string
     pub fn generate_page_from_hierarchical(&self, s: &str) -> Option<Page> {
-        let h = HierarchicalAddress::from_display_string(s)?;
-        let location = Location::from_hierarchical(h);
+        let h = HierarchicalAddress::from_display_string(s, &self.geometry)?;
+        let location = Location::from_hierarchical(h, &self.geometry)?;
         Some(self.generate_page(&location))
     }
 
     /// Search for exact text, returns location where it appears at start
     pub fn search(&self, query: &str) -> Option<SearchResult> {
+        self.search_at(query, 0)
+    }
+
+    /// Search for exact text starting at cluster `position`, with blank surroundings
+    pub fn search_at(&self, query: &str, position: usize) -> Option<SearchResult> {
+        self.search_placed(query, Placement::Position { position })
+    }
+
+    /// Search for exact text placed by line and column, or aligned on a line or the page
+    pub fn search_placed(&self, query: &str, placement: Placement) -> Option<SearchResult> {
+        // Blank filler never draws from the rng
+        self.search_filled_with(query, placement, Filler::Blank, &mut seeded_rng(0))
+    }
+
+    /// Search for placed text, surrounded by `filler`
+    pub fn search_filled(
+        &self,
+        query: &str,
+        placement: Placement,
+        filler: Filler,
+    ) -> Option<SearchResult> {
+        self.search_filled_with(query, placement, filler, &mut entropy_rng())
+    }
+
+    /// Search for placed text, surrounded by `filler` drawn from `rng`
+    pub fn search_filled_with<R: Rng + ?Sized>(
+        &self,
+        query: &str,
+        placement: Placement,
+        filler: Filler,
+        rng: &mut R,
+    ) -> Option<SearchResult> {
         let query_indices = self.alphabet.segment(query)?;
-        if query_indices.is_empty() { return None; }
+        if query_indices.is_empty() {
+            return None;
+        }
+        let position = placement.resolve(query_indices.len(), &self.geometry)?;
+        if position.checked_add(query_indices.len())? > self.geometry.clusters_per_page {
+            return None;
+        }
+
+        let content_indices = filler.fill(
+            &query_indices,
+            position,
+            self.geometry.clusters_per_page,
+            &self.weights,
+            rng,
+        );
+        Some(self.search_result(query, &content_indices, position, query_indices.len()))
+    }
+
+    /// Search for the occurrence of `query` with the smallest address, trying up to
+    /// `budget` query positions (from the end of the page backwards)
+    ///
+    /// For each position the smallest address is found exactly, since the content
+    /// digits from there to the end of the page are a linear function of the
+    /// address mod N^k (see [`BijectionEngine::smallest_address_with`]). The best
+    /// address is usually around N^len, so short queries land in rooms whose
+    /// Kannada names are only a few clusters long.
+    pub fn search_shortest(&self, query: &str, budget: usize) -> Option<SearchResult> {
+        let query_indices = self.alphabet.segment(query)?;
+        let page_length = self.geometry.clusters_per_page;
+        if query_indices.is_empty() || query_indices.len() > page_length {
+            return None;
+        }
+
+        let window = self.bijection.indices_to_biguint(&query_indices);
+        let width = query_indices.len() as u32;
+        let last_position = page_length - query_indices.len();
+
+        let (address, position) = (0..=last_position)
+            .take(budget.max(1))
+            .filter_map(|below| {
+                let address = self.bijection.smallest_address_with(&window, width, below as u32)?;
+                Some((address, last_position - below))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0))?;
+
+        let content_num = self.bijection.address_to_content(&address);
+        let content_indices = self.bijection.biguint_to_indices(content_num, page_length);
+        Some(self.search_result(query, &content_indices, position, query_indices.len()))
+    }
+
+    /// Search for a page at an address matching `address` that has `text` at
+    /// `position` (or at any position, tried from the end of the page)
+    ///
+    /// Both constraints are linear in the address: fixed levels make it
+    /// mandira × R + offset for a set of in-room offsets, and fixed content
+    /// digits make address × inverse fall in an interval mod N^k. Each run of
+    /// consecutive candidate addresses (or, with the mandira free, each offset)
+    /// is then one "smallest x with lo ≤ a·x mod m ≤ hi" problem, so
+    /// `Impossible` is a proof that no such page exists.
+    ///
+    /// Loose constraints can mean many problems, so the search stops with
+    /// `BudgetExceeded` after [`MAX_CONSTRAINED_WORK`] digits of solving.
+    /// None if the text or a fixed level is invalid.
+    pub fn search_constrained(
+        &self,
+        address: &AddressConstraint,
+        text: &str,
+        position: Option<usize>,
+    ) -> Option<ConstrainedSearch> {
+        let query_indices = self.alphabet.segment(text)?;
+        let page_length = self.geometry.clusters_per_page;
+        if query_indices.is_empty()
+            || query_indices.len() > page_length
+            || !address.is_valid(&self.geometry)
+            || address.mandira.as_ref().is_some_and(|m| *m >= self.room_count())
+        {
+            return None;
+        }
+
+        let last_position = page_length - query_indices.len();
+        let positions: Vec<usize> = match position {
+            Some(position) if position <= last_position => vec![position],
+            Some(_) => return None,
+            None => (0..=last_position).rev().collect(),
+        };
+
+        let window = self.bijection.indices_to_biguint(&query_indices);
+        let width = query_indices.len() as u32;
+        let offsets = self.constrained_offsets(address);
+
+        let mut work = 0;
+        for position in positions {
+            let below = (last_position - position) as u32;
+            let residues = self.bijection.window_residues(&window, width, below)?;
+            let cost = (width + below) as u64;
+
+            let found = match self.constrained_address(address, &offsets, &residues, cost, &mut work) {
+                Ok(found) => found,
+                Err(OutOfWork) => return Some(ConstrainedSearch::BudgetExceeded),
+            };
+            if let Some(raw) = found {
+                let content_num = self.bijection.address_to_content(&raw);
+                let content_indices = self.bijection.biguint_to_indices(content_num, page_length);
+                let result = self.search_result(text, &content_indices, position, query_indices.len());
+                return Some(ConstrainedSearch::Found(result));
+            }
+        }
+        Some(ConstrainedSearch::Impossible)
+    }
 
-        let mut padded = query_indices.clone();
-        padded.resize(CLUSTERS_PER_PAGE, 0);
+    /// Runs of in-room offsets allowed by the fixed levels, as (start, length)
+    fn constrained_offsets(&self, address: &AddressConstraint) -> Vec<(u64, u64)> {
+        let g = &self.geometry;
+        let levels = [
+            (address.gode, g.walls_per_room, g.pages_per_wall()),
+            (address.patti, g.shelves_per_wall, g.pages_per_shelf()),
+            (address.pustaka, g.books_per_shelf, g.pages_per_book as u64),
+            (address.puta, g.pages_per_book, 1),
+        ];
 
-        let content_num = self.bijection.indices_to_biguint(&padded);
+        // Levels below the finest fixed one are free, so each run covers them
+        let Some(finest) = levels.iter().rposition(|(fixed, _, _)| fixed.is_some()) else {
+            return vec![(0, g.pages_per_room())];
+        };
+        let run_length = levels[finest].2;
+
+        let mut starts = vec![0u64];
+        for &(fixed, count, stride) in &levels[..=finest] {
+            let values: Vec<u64> = match fixed {
+                Some(value) => vec![(value - 1) as u64],
+                None => (0..count as u64).collect(),
+            };
+            starts = starts
+                .iter()
+                .flat_map(|start| values.iter().map(move |v| start + v * stride))
+                .collect();
+        }
+        starts.into_iter().map(|start| (start, run_length)).collect()
+    }
+
+    /// Smallest-first address within the constraints whose content residue is
+    /// in [lo, hi], charging `cost` to `work` for each problem solved
+    fn constrained_address(
+        &self,
+        address: &AddressConstraint,
+        offsets: &[(u64, u64)],
+        (m, inverse, lo, hi): &(BigUint, BigUint, BigUint, BigUint),
+        cost: u64,
+        work: &mut u64,
+    ) -> Result<Option<BigUint>, OutOfWork> {
+        let pages_per_room = BigUint::from(self.geometry.pages_per_room());
+        let modulus = self.bijection.modulus();
+        let mut charge = || {
+            *work += cost;
+            if *work > MAX_CONSTRAINED_WORK {
+                Err(OutOfWork)
+            } else {
+                Ok(())
+            }
+        };
+
+        match &address.mandira {
+            // Each run is consecutive addresses: solve for the step into the run
+            Some(mandira) => {
+                for &(start, length) in offsets {
+                    charge()?;
+                    let first = mandira * &pages_per_room + start;
+                    let Some(x) = BijectionEngine::smallest_from(&(&first * inverse), inverse, m, lo, hi)
+                    else {
+                        continue;
+                    };
+                    let raw = first + &x;
+                    if x < BigUint::from(length) && raw < *modulus {
+                        return Ok(Some(raw));
+                    }
+                }
+                Ok(None)
+            }
+            // Nothing fixed: every address is a candidate
+            None if offsets == [(0, self.geometry.pages_per_room())] => {
+                charge()?;
+                Ok(BijectionEngine::smallest_in_range(inverse, m, lo, hi))
+            }
+            // Otherwise solve for the room, one in-room offset at a time
+            None => {
+                let room_step = &pages_per_room * inverse;
+                for offset in offsets.iter().flat_map(|&(start, length)| start..start + length) {
+                    charge()?;
+                    let base = BigUint::from(offset) * inverse;
+                    let Some(room) = BijectionEngine::smallest_from(&base, &room_step, m, lo, hi)
+                    else {
+                        continue;
+                    };
+                    let raw = room * &pages_per_room + offset;
+                    if raw < *modulus {
+                        return Ok(Some(raw));
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Every distinct page containing `query`, in a fixed order
+    ///
+    /// See [`Occurrences`] for the order and for resuming from a token.
+    pub fn occurrences(&self, query: &str) -> Option<Occurrences<'_>> {
+        self.occurrences_from(query, BigUint::zero())
+    }
+
+    /// Same as [`occurrences`](Self::occurrences), resuming at a pagination token
+    pub fn occurrences_from(&self, query: &str, token: BigUint) -> Option<Occurrences<'_>> {
+        let query_indices = self.alphabet.segment(query)?;
+        if query_indices.is_empty() || query_indices.len() > self.geometry.clusters_per_page {
+            return None;
+        }
+
+        let positions = self.geometry.clusters_per_page - query_indices.len() + 1;
+        let free_slots = self.geometry.clusters_per_page - query_indices.len();
+        let end = BigUint::from(positions) * BigUint::from(self.alphabet.size()).pow(free_slots as u32);
+
+        Some(Occurrences {
+            library: self,
+            query: query.to_string(),
+            query_indices,
+            positions,
+            next: token,
+            end,
+        })
+    }
+
+    /// Search for the first text matching `pattern`, with blank surroundings
+    ///
+    /// The chosen text is reported as the result's `query`.
+    pub fn search_pattern(
+        &self,
+        pattern: &str,
+        placement: Placement,
+    ) -> Result<SearchResult, PatternError> {
+        let pattern = Pattern::parse(pattern, &self.alphabet)?;
+        let chosen = pattern
+            .first_match(self.geometry.clusters_per_page)
+            .ok_or(PatternError::DoesNotFit)?;
+        self.place_pattern_match(&chosen, placement, Filler::Blank, &mut seeded_rng(0))
+    }
+
+    /// Search for a random text matching `pattern`, surrounded by `filler`
+    pub fn search_pattern_random(
+        &self,
+        pattern: &str,
+        placement: Placement,
+        filler: Filler,
+    ) -> Result<SearchResult, PatternError> {
+        self.search_pattern_random_with(pattern, placement, filler, &mut entropy_rng())
+    }
+
+    /// Same as [`search_pattern_random`](Self::search_pattern_random), drawing from `rng`
+    pub fn search_pattern_random_with<R: Rng + ?Sized>(
+        &self,
+        pattern: &str,
+        placement: Placement,
+        filler: Filler,
+        rng: &mut R,
+    ) -> Result<SearchResult, PatternError> {
+        let pattern = Pattern::parse(pattern, &self.alphabet)?;
+        let chosen = pattern
+            .random_match(self.geometry.clusters_per_page, rng)
+            .ok_or(PatternError::DoesNotFit)?;
+        self.place_pattern_match(&chosen, placement, filler, rng)
+    }
+
+    fn place_pattern_match<R: Rng + ?Sized>(
+        &self,
+        chosen: &[usize],
+        placement: Placement,
+        filler: Filler,
+        rng: &mut R,
+    ) -> Result<SearchResult, PatternError> {
+        let position = placement
+            .resolve(chosen.len(), &self.geometry)
+            .ok_or(PatternError::DoesNotFit)?;
+
+        let content_indices =
+            filler.fill(chosen, position, self.geometry.clusters_per_page, &self.weights, rng);
+        let text = self.alphabet.indices_to_string(chosen);
+        Ok(self.search_result(&text, &content_indices, position, chosen.len()))
+    }
+
+    /// Compose a page with each `(position, text)` fragment in place, filled elsewhere by `filler`
+    pub fn compose_page(
+        &self,
+        fragments: &[(usize, &str)],
+        filler: Filler,
+    ) -> Result<Location, ComposeError> {
+        self.compose_page_with(fragments, filler, &mut entropy_rng())
+    }
+
+    /// Same as [`compose_page`](Self::compose_page), drawing filler from `rng`
+    pub fn compose_page_with<R: Rng + ?Sized>(
+        &self,
+        fragments: &[(usize, &str)],
+        filler: Filler,
+        rng: &mut R,
+    ) -> Result<Location, ComposeError> {
+        if fragments.is_empty() {
+            return Err(ComposeError::NoFragments);
+        }
+
+        let mut segmented = Vec::with_capacity(fragments.len());
+        for (index, &(position, text)) in fragments.iter().enumerate() {
+            let indices = self
+                .alphabet
+                .segment(text)
+                .filter(|indices| !indices.is_empty())
+                .ok_or(ComposeError::InvalidText { index })?;
+            if position
+                .checked_add(indices.len())
+                .is_none_or(|end| end > self.geometry.clusters_per_page)
+            {
+                return Err(ComposeError::OutOfBounds { index });
+            }
+            segmented.push((position, indices));
+        }
+
+        for (first, (a_start, a)) in segmented.iter().enumerate() {
+            for (second, (b_start, b)) in segmented.iter().enumerate().skip(first + 1) {
+                if a_start < &(b_start + b.len()) && b_start < &(a_start + a.len()) {
+                    return Err(ComposeError::Overlap { first, second });
+                }
+            }
+        }
+
+        let fixed: Vec<(usize, &[usize])> = segmented
+            .iter()
+            .map(|(position, indices)| (*position, indices.as_slice()))
+            .collect();
+        let content_indices =
+            filler.compose(&fixed, self.geometry.clusters_per_page, &self.weights, rng);
+
+        let content_num = self.bijection.indices_to_biguint(&content_indices);
+        let raw_address = self.bijection.content_to_address(&content_num);
+        Ok(Location::from_raw_address(&raw_address, &self.geometry))
+    }
+
+    fn search_result(
+        &self,
+        query: &str,
+        content_indices: &[usize],
+        position: usize,
+        cluster_count: usize,
+    ) -> SearchResult {
+        let content_num = self.bijection.indices_to_biguint(content_indices);
         let raw_address = self.bijection.content_to_address(&content_num);
-        let location = Location::from_raw_address(&raw_address);
+        let location = Location::from_raw_address(&raw_address, &self.geometry);
 
-        Some(SearchResult {
+        SearchResult {
             query: query.to_string(),
             location,
-            cluster_count: query_indices.len(),
-        })
+            cluster_count,
+            span: self.match_span(content_indices, position, cluster_count),
+        }
     }
 
     /// Check if a string contains Kannada characters
@@ -102,48 +592,55 @@ impl LibraryOfBabel {
         s.chars().any(|c| ('\u{0C80}'..='\u{0CFF}').contains(&c))
     }
 
-     /// Parse address that may contain Kannada mandira
+    /// Parse address that may contain Kannada mandira
     /// Accepts: hex, hierarchical with hex mandira, or hierarchical with Kannada mandira
     pub fn parse_address(&self, address: &str) -> Option<Location> {
-        if address.contains('.') {
-            // Hierarchical format: mandira.gode.patti.pustaka.puta
-            let parts: Vec<&str> = address.rsplitn(5, '.').collect();
-            
-            if parts.len() == 5 {
-                // parts are in reverse: [puta, pustaka, patti, gode, mandira]
-                let mandira_str = parts[4];
-                
-                // Convert Kannada mandira to hex if needed
-                let mandira_hex = if Self::contains_kannada(mandira_str) {
-                    self.kannada_to_hex(mandira_str)?
-                } else {
-                    mandira_str.to_string()
-                };
-                
-                // Reconstruct the hierarchical string with hex mandira
-                let hex_address = format!(
-                    "{}.{}.{}.{}.{}",
-                    mandira_hex, parts[3], parts[2], parts[1], parts[0]
-                );
-                
-                HierarchicalAddress::from_display_string(&hex_address)
-                    .map(Location::from_hierarchical)
-            } else {
-                // Try directly
-                HierarchicalAddress::from_display_string(address)
-                    .map(Location::from_hierarchical)
-            }
-        } else if Self::contains_kannada(address) {
-            // Raw Kannada address (no dots)
-            let hex = self.kannada_to_hex(address)?;
-            Location::from_hex(&hex)
-        } else {
+        if let Some(location) = self.parse_hierarchical(address) {
+            Some(location)
+        } else if Self::is_hex(address) {
             // Raw hex address
-            Location::from_hex(address)
+            Location::from_hex(address, &self.geometry)
+        } else {
+            // Raw Kannada address, using the same naming scheme as rooms
+            let raw = self.mandira_from_kannada(address)?;
+            Some(Location::from_raw_address(&raw, &self.geometry))
         }
     }
 
-    /// Convert Kannada mandira string to hex
+    /// Hierarchical format: mandira.gode.patti.pustaka.puta
+    fn parse_hierarchical(&self, address: &str) -> Option<Location> {
+        // A Kannada mandira may itself contain '.', so split from the right
+        let parts: Vec<&str> = address.rsplitn(5, '.').collect();
+        if parts.len() != 5 { return None; }
+
+        // parts are in reverse: [puta, pustaka, patti, gode, mandira]
+        let mandira_str = parts[4];
+        let mandira = if Self::is_hex(mandira_str) {
+            BigUint::parse_bytes(mandira_str.as_bytes(), 16)?
+        } else {
+            self.mandira_from_kannada(mandira_str)?
+        };
+
+        let h = HierarchicalAddress {
+            mandira,
+            gode: parts[3].parse().ok()?,
+            patti: parts[2].parse().ok()?,
+            pustaka: parts[1].parse().ok()?,
+            puta: parts[0].parse().ok()?,
+        };
+        Location::from_hierarchical(h, &self.geometry)
+    }
+
+    fn is_hex(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit())
+    }
+
+    /// Convert Kannada text to hex, reading its clusters as a positional
+    /// base-N number
+    ///
+    /// This is not the room naming of [`Mandira`]: leading spaces are
+    /// dropped, so it is not one-to-one. Raw Kannada addresses are parsed
+    /// with [`mandira_from_kannada`](Self::mandira_from_kannada) instead.
     pub fn kannada_to_hex(&self, kannada: &str) -> Option<String> {
         let indices = self.alphabet.segment(kannada)?;
         if indices.is_empty() {
@@ -153,120 +650,619 @@ impl LibraryOfBabel {
         Some(num.to_str_radix(16))
     }
 
+    /// Parse a Kannada mandira name (see [`Mandira`])
+    pub fn mandira_from_kannada(&self, name: &str) -> Option<BigUint> {
+        Mandira::from_kannada(name, &self.alphabet)
+    }
+
+    /// Number of pages in the library, N^P
+    pub fn page_count(&self) -> &BigUint {
+        self.bijection.modulus()
+    }
+
+    /// Number of rooms in the library (the last one is only partly filled)
+    pub fn room_count(&self) -> BigUint {
+        self.topology.room_count().clone()
+    }
+
+    /// Rooms connected to `mandira` by staircase and corridors
+    pub fn neighbours(&self, mandira: &BigUint) -> Neighbours {
+        self.topology.neighbours(mandira)
+    }
+
+    /// Walk out of the room in `direction`, keeping the same wall, shelf, book and page
+    pub fn walk(&self, location: &Location, direction: Direction) -> Location {
+        let mut h = location.hierarchical.clone();
+        h.mandira = self.topology.neighbour(&h.mandira, direction);
+        Location::from_raw_address(&h.to_raw(&self.geometry), &self.geometry)
+    }
 
     /// Search for text at a random position with random surrounding content
     pub fn search_at_random_position(&self, query: &str) -> Option<SearchResult> {
+        self.search_at_random_position_with(query, &mut entropy_rng())
+    }
+
+    /// Same as [`search_at_random_position`](Self::search_at_random_position), reproducible from a seed
+    pub fn search_at_random_position_seeded(&self, query: &str, seed: u64) -> Option<SearchResult> {
+        self.search_at_random_position_with(query, &mut seeded_rng(seed))
+    }
+
+    /// Search for text at a random position, drawing from `rng`
+    pub fn search_at_random_position_with<R: Rng + ?Sized>(
+        &self,
+        query: &str,
+        rng: &mut R,
+    ) -> Option<SearchResult> {
         let query_indices = self.alphabet.segment(query)?;
-        if query_indices.is_empty() || query_indices.len() >= CLUSTERS_PER_PAGE {
+        if query_indices.is_empty() || query_indices.len() > self.geometry.clusters_per_page {
             return None;
         }
 
-        let max_position = CLUSTERS_PER_PAGE - query_indices.len();
+        let positions = self.geometry.clusters_per_page - query_indices.len() + 1;
+        let position = random_index(rng, positions);
+
+        self.search_filled_with(query, Placement::Position { position }, Filler::Uniform, rng)
+    }
+
+    /// Generate a random page
+    pub fn random_page(&self) -> Page {
+        self.random_page_with(&mut entropy_rng())
+    }
 
-        let state = RandomState::new();
-        let now = get_timestamp_nanos();
+    /// Same as [`random_page`](Self::random_page), reproducible from a seed
+    pub fn random_page_seeded(&self, seed: u64) -> Page {
+        self.random_page_with(&mut seeded_rng(seed))
+    }
 
-        let mut hasher = state.build_hasher();
-        now.hash(&mut hasher);
-        let random_val = hasher.finish();
+    /// Generate a random page, drawing from `rng`
+    pub fn random_page_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Page {
+        self.generate_page(&self.random_location_with(rng))
+    }
 
-        let position = (random_val as usize) % (max_position + 1);
+    /// Uniformly random location over the whole library, drawing from `rng`
+    pub fn random_location_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Location {
+        let raw = random_below(rng, self.bijection.modulus());
+        Location::from_raw_address(&raw, &self.geometry)
+    }
 
-        let mut content_indices = Vec::with_capacity(CLUSTERS_PER_PAGE);
+    /// Uniformly random location in the same book, shelf, wall or room as `scope`
+    pub fn random_location_in(&self, scope: &Location, level: Level) -> Option<Location> {
+        self.random_location_in_with(scope, level, &mut entropy_rng())
+    }
 
-        // Random prefix
-        for i in 0..position {
-            let mut h = state.build_hasher();
-            (now + i as u128).hash(&mut h);
-            let idx = (h.finish() as usize) % self.alphabet.size();
-            content_indices.push(idx);
+    /// Uniformly random location in the same `level` unit as `scope`, drawing from `rng`
+    ///
+    /// The last room is only partly inside the library, so sampling is clipped to
+    /// real pages. Returns None when `scope` lies outside the library.
+    pub fn random_location_in_with<R: Rng + ?Sized>(
+        &self,
+        scope: &Location,
+        level: Level,
+        rng: &mut R,
+    ) -> Option<Location> {
+        let span = level.pages(&self.geometry);
+        let start = scope.to_raw() / &span * &span;
+        let modulus = self.bijection.modulus();
+        if start >= *modulus {
+            return None;
         }
 
-        // Insert query
-        content_indices.extend_from_slice(&query_indices);
+        let available = span.min(modulus - &start);
+        let raw = start + random_below(rng, &available);
+        Some(Location::from_raw_address(&raw, &self.geometry))
+    }
 
-        // Random suffix
-        for i in (position + query_indices.len())..CLUSTERS_PER_PAGE {
-            let mut h = state.build_hasher();
-            (now + i as u128 + 999999).hash(&mut h);
-            let idx = (h.finish() as usize) % self.alphabet.size();
-            content_indices.push(idx);
+    /// Verify that content matches address
+    pub fn verify(&self, location: &Location, expected_start: &str) -> bool {
+        let page = self.generate_page(location);
+        page.content.starts_with(expected_start)
+    }
+
+    /// Check where `text` appears on the page at `location`, with a diff when
+    /// it doesn't and the arithmetic to recheck the page independently
+    pub fn verify_detailed(&self, location: &Location, text: &str) -> Option<Verification> {
+        let expected = self.alphabet.segment(text)?;
+        if expected.is_empty() {
+            return None;
         }
 
+        let page = self.generate_page(location);
+        let actual = &page.cluster_indices;
+
+        let occurrences: Vec<MatchSpan> = actual
+            .windows(expected.len())
+            .enumerate()
+            .filter(|(_, window)| *window == expected.as_slice())
+            .map(|(position, _)| self.match_span(actual, position, expected.len()))
+            .collect();
+
+        let diff = occurrences.is_empty().then(|| self.verify_diff(&expected, actual));
+
+        Some(Verification {
+            at_start: actual.starts_with(&expected),
+            found: !occurrences.is_empty(),
+            occurrences,
+            diff,
+            proof: self.proof(&page),
+        })
+    }
+
+    /// Align `expected` where most clusters agree (earliest on ties), then compare
+    fn verify_diff(&self, expected: &[usize], actual: &[usize]) -> VerifyDiff {
+        let cluster = |i: usize| self.alphabet.get(i).unwrap_or("").to_string();
+        let last_position = actual.len().saturating_sub(expected.len());
+
+        let matching_at = |position: usize| {
+            expected
+                .iter()
+                .zip(&actual[position..])
+                .filter(|(e, a)| e == a)
+                .count()
+        };
+        let position = (0..=last_position)
+            .max_by_key(|&p| (matching_at(p), std::cmp::Reverse(p)))
+            .unwrap_or(0);
+
+        let clusters = expected
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| {
+                let a = actual.get(position + i).copied();
+                ClusterComparison {
+                    expected: cluster(e),
+                    actual: a.map(cluster).unwrap_or_default(),
+                    equal: a == Some(e),
+                }
+            })
+            .collect();
+
+        VerifyDiff { position, matching_clusters: matching_at(position), clusters }
+    }
+
+    /// The content number, multiplier and modular product behind a page
+    pub fn proof(&self, page: &Page) -> BijectionProof {
+        let content = self.bijection.indices_to_biguint(&page.cluster_indices);
+        let product = &content * self.bijection.multiplier();
+        let address = &product % self.bijection.modulus();
+
+        BijectionProof {
+            alphabet_size: self.alphabet.size(),
+            cluster_indices: page.cluster_indices.clone(),
+            content: content.to_string(),
+            multiplier: self.bijection.multiplier().to_string(),
+            modulus: self.bijection.modulus().to_string(),
+            product: product.to_string(),
+            address_hex: address.to_str_radix(16),
+            address: address.to_string(),
+        }
+    }
+
+    /// Most bytes one page can hold with [`encode_bytes`](Self::encode_bytes)
+    pub fn byte_capacity(&self) -> usize {
+        // The length header is a u16, which caps pages of very large geometries
+        self.page_byte_width().saturating_sub(BYTE_LENGTH_HEADER).min(u16::MAX as usize)
+    }
+
+    /// Whole bytes in a content number: 256^width ≤ N^page
+    fn page_byte_width(&self) -> usize {
+        ((self.bijection.modulus().bits() - 1) / 8) as usize
+    }
+
+    /// Hide up to [`byte_capacity`](Self::byte_capacity) bytes on a page
+    ///
+    /// The content number is a 2-byte big-endian length, then the data, then
+    /// zero padding, read as one big-endian number; the same bytes always give
+    /// the same page.
+    pub fn encode_bytes(&self, data: &[u8]) -> Option<Location> {
+        if data.len() > self.byte_capacity() {
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity(self.page_byte_width());
+        bytes.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes.resize(self.page_byte_width(), 0);
+
+        let content_num = BigUint::from_bytes_be(&bytes);
+        let raw_address = self.bijection.content_to_address(&content_num);
+        Some(Location::from_raw_address(&raw_address, &self.geometry))
+    }
+
+    /// Bytes hidden by [`encode_bytes`](Self::encode_bytes), or None if the page
+    /// isn't such an encoding
+    pub fn decode_bytes(&self, location: &Location) -> Option<Vec<u8>> {
+        let content_num = self.bijection.address_to_content(&location.to_raw());
+        let width = self.page_byte_width();
+
+        let digits = content_num.to_bytes_be();
+        if digits.len() > width {
+            return None;
+        }
+        let mut bytes = vec![0u8; width - digits.len()];
+        bytes.extend_from_slice(&digits);
+
+        let length = u16::from_be_bytes([*bytes.first()?, *bytes.get(1)?]) as usize;
+        let payload = &bytes[BYTE_LENGTH_HEADER..];
+        let (data, padding) = payload.split_at_checked(length)?;
+        padding.iter().all(|&b| b == 0).then(|| data.to_vec())
+    }
+
+    /// Hide data of any length, one page per [`byte_capacity`](Self::byte_capacity) bytes
+    ///
+    /// The pages are unrelated rather than consecutive (see
+    /// [`generate_book`](Self::generate_book) for why), so keep the returned
+    /// list of locations to get the data back. None only
+    /// for geometries whose pages can't hold a single byte.
+    pub fn encode_file(&self, data: &[u8]) -> Option<Vec<Location>> {
+        if data.is_empty() {
+            return Some(vec![self.encode_bytes(data)?]);
+        }
+        if self.byte_capacity() == 0 {
+            return None;
+        }
+        data.chunks(self.byte_capacity())
+            .map(|chunk| self.encode_bytes(chunk))
+            .collect()
+    }
+
+    /// Data hidden by [`encode_file`](Self::encode_file), from its locations in order
+    pub fn decode_file(&self, locations: &[Location]) -> Option<Vec<u8>> {
+        let mut data = Vec::new();
+        for location in locations {
+            data.extend(self.decode_bytes(location)?);
+        }
+        Some(data)
+    }
+
+    /// Longest message (in clusters) [`hide_message`](Self::hide_message) can place
+    pub fn message_capacity(&self) -> usize {
+        cipher::message_capacity(self.geometry.clusters_per_page, self.alphabet.size())
+    }
+
+    /// Encrypt `text` with a passphrase and place it on a fresh random page
+    ///
+    /// The page is indistinguishable from any other without the passphrase.
+    /// None if the text is empty, isn't Kannada or is longer than
+    /// [`message_capacity`](Self::message_capacity).
+    pub fn hide_message(&self, text: &str, passphrase: &str) -> Option<Location> {
+        self.hide_message_with(text, passphrase, &mut entropy_rng())
+    }
+
+    /// Same as [`hide_message`](Self::hide_message), drawing the nonce and padding from `rng`
+    pub fn hide_message_with<R: Rng + ?Sized>(
+        &self,
+        text: &str,
+        passphrase: &str,
+        rng: &mut R,
+    ) -> Option<Location> {
+        let message = self.alphabet.segment(text).filter(|m| !m.is_empty())?;
+        let content_indices = cipher::seal(
+            &message,
+            passphrase,
+            self.geometry.clusters_per_page,
+            self.alphabet.size(),
+            rng,
+        )?;
+
         let content_num = self.bijection.indices_to_biguint(&content_indices);
         let raw_address = self.bijection.content_to_address(&content_num);
-        let location = Location::from_raw_address(&raw_address);
+        Some(Location::from_raw_address(&raw_address, &self.geometry))
+    }
+
+    /// Text hidden by [`hide_message`](Self::hide_message), or None if the
+    /// passphrase is wrong or the page holds no message
+    pub fn reveal(&self, location: &Location, passphrase: &str) -> Option<String> {
+        let page = self.generate_page(location);
+        let message = cipher::open(&page.cluster_indices, passphrase, self.alphabet.size())?;
+        Some(self.alphabet.indices_to_string(&message))
+    }
+
+    /// The page for `date`, the same for every visitor that day
+    ///
+    /// SHA-256 of the date and an optional `salt` seeds the generator that
+    /// picks a uniformly random address, so the server and the browser agree
+    /// and a different salt gives an unrelated series of pages.
+    pub fn page_of_the_day(&self, date: &CalendarDate, salt: Option<&str>) -> Page {
+        let mut hasher = Sha256::new();
+        hasher.update(b"akshara-mantapa/daily\0");
+        hasher.update(date.to_string().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(salt.unwrap_or("").as_bytes());
+
+        let mut rng = LibraryRng::from_seed(hasher.finalize().into());
+        self.random_page_with(&mut rng)
+    }
+
+    /// Score a page against a coherence model
+    pub fn score_page(&self, page: &Page, model: &CoherenceModel) -> PageScore {
+        model.score(&page.cluster_indices, &self.alphabet)
+    }
+
+    /// Sample `samples` random pages and keep the `keep` most coherent, best first
+    pub fn find_coherent_pages(
+        &self,
+        model: &CoherenceModel,
+        samples: usize,
+        keep: usize,
+    ) -> Vec<ScoredPage> {
+        self.find_coherent_pages_with(model, samples, keep, &mut entropy_rng())
+    }
+
+    pub fn find_coherent_pages_with<R: Rng + ?Sized>(
+        &self,
+        model: &CoherenceModel,
+        samples: usize,
+        keep: usize,
+        rng: &mut R,
+    ) -> Vec<ScoredPage> {
+        let mut best: Vec<ScoredPage> = Vec::with_capacity(keep + 1);
+
+        for _ in 0..samples {
+            let page = self.random_page_with(rng);
+            let score = self.score_page(&page, model);
+            let worst = best.last().map(|kept| &kept.score);
+            if best.len() == keep && worst.is_none_or(|worst| score.compare(worst).is_le()) {
+                continue;
+            }
+
+            let at = best.partition_point(|kept| kept.score.compare(&score).is_ge());
+            best.insert(at, ScoredPage { location: page.location, content: page.content, score });
+            best.truncate(keep);
+        }
+        best
+    }
 
-        Some(SearchResult {
+    /// Cluster frequencies, kinds, entropy and runs over `pages`, with the
+    /// `top` most common clusters
+    pub fn cluster_stats(&self, pages: &[Page], top: usize) -> ClusterStats {
+        let indices: Vec<&[usize]> = pages.iter().map(|page| page.cluster_indices.as_slice()).collect();
+        ClusterStats::compute(&indices, &self.alphabet, top)
+    }
+
+    /// Cluster statistics over `count` random pages
+    pub fn sample_stats_with<R: Rng + ?Sized>(&self, count: usize, top: usize, rng: &mut R) -> ClusterStats {
+        let pages: Vec<Page> = (0..count).map(|_| self.random_page_with(rng)).collect();
+        self.cluster_stats(&pages, top)
+    }
+
+    /// Exact number of pages holding `query` at the start and anywhere, and
+    /// the odds of a random page doing so (None if the text isn't Kannada)
+    pub fn text_probability(&self, query: &str) -> Option<TextProbability> {
+        let indices = self.alphabet.segment(query).filter(|indices| !indices.is_empty())?;
+        Some(TextProbability::new(
+            query,
+            &indices,
+            self.alphabet.size(),
+            self.geometry.clusters_per_page,
+        ))
+    }
+
+    /// Every step from `query` to its address, as [`search`](Self::search) finds it
+    pub fn explain_search(&self, query: &str) -> Option<SearchExplanation> {
+        let query_indices = self.alphabet.segment(query).filter(|indices| !indices.is_empty())?;
+        let page_length = self.geometry.clusters_per_page;
+        if query_indices.len() > page_length {
+            return None;
+        }
+
+        let mut digits = query_indices.clone();
+        digits.resize(page_length, 0);
+        let content_num = self.bijection.indices_to_biguint(&digits);
+        let (multiplication, raw_address) =
+            self.modular_step(&content_num, self.bijection.multiplier());
+
+        Some(SearchExplanation {
             query: query.to_string(),
-            location,
-            cluster_count: query_indices.len(),
+            alphabet_size: self.alphabet.size(),
+            page_length,
+            clusters: self.explain_clusters(&query_indices),
+            digits,
+            content_num: content_num.to_string(),
+            multiplication,
+            address: self.hierarchy_split(&raw_address),
         })
     }
 
-    /// Generate a random page
-    pub fn random_page(&self) -> Page {
-        let state = RandomState::new();
-        let now = get_timestamp_nanos();
-
-        let mut hasher = state.build_hasher();
-        now.hash(&mut hasher);
-        let h1 = hasher.finish();
+    /// Every step from an address to the text of its page
+    pub fn explain_page(&self, location: &Location) -> PageExplanation {
+        let raw_address = location.to_raw();
+        let (multiplication, content_num) = self.modular_step(&raw_address, self.bijection.inverse());
+        let page = self.page_from_content(location, content_num.clone());
 
-        let mut hasher2 = state.build_hasher();
-        h1.hash(&mut hasher2);
-        let h2 = hasher2.finish();
+        PageExplanation {
+            alphabet_size: self.alphabet.size(),
+            page_length: self.geometry.clusters_per_page,
+            address: self.hierarchy_split(&raw_address),
+            multiplication,
+            content_num: content_num.to_string(),
+            clusters: self.explain_clusters(&page.cluster_indices),
+            digits: page.cluster_indices,
+            content: page.content,
+        }
+    }
 
-        let random_seed = format!("{:016x}{:016x}{:016x}{:016x}", 
-            h1, h2, h1 ^ h2, h1.wrapping_add(h2));
-        let raw = BigUint::parse_bytes(random_seed.as_bytes(), 16)
-            .unwrap_or_else(BigUint::zero);
+    fn explain_clusters(&self, indices: &[usize]) -> Vec<ExplainedCluster> {
+        indices
+            .iter()
+            .enumerate()
+            .map(|(position, &index)| ExplainedCluster {
+                position,
+                text: self.alphabet.get(index).unwrap_or("").to_string(),
+                index,
+            })
+            .collect()
+    }
 
-        let raw = raw % self.bijection.modulus();
-        let location = Location::from_raw_address(&raw);
+    /// `operand × factor` reduced modulo N^page_length, and the remainder
+    fn modular_step(&self, operand: &BigUint, factor: &BigUint) -> (ModularStep, BigUint) {
+        let product = operand * factor;
+        let (quotient, remainder) = product.div_rem(self.bijection.modulus());
 
-        self.generate_page(&location)
+        let step = ModularStep {
+            operand: operand.to_string(),
+            factor: factor.to_string(),
+            product: product.to_string(),
+            modulus: self.bijection.modulus().to_string(),
+            quotient: quotient.to_string(),
+            remainder: remainder.to_string(),
+        };
+        (step, remainder)
     }
 
-    /// Verify that content matches address
-    pub fn verify(&self, location: &Location, expected_start: &str) -> bool {
-        let page = self.generate_page(location);
-        page.content.starts_with(expected_start)
+    /// The divisions of [`HierarchicalAddress::from_raw`], with every intermediate value
+    fn hierarchy_split(&self, raw_address: &BigUint) -> HierarchySplit {
+        let levels = [
+            ("puta", self.geometry.pages_per_book),
+            ("pustaka", self.geometry.books_per_shelf),
+            ("patti", self.geometry.shelves_per_wall),
+            ("gode", self.geometry.walls_per_room),
+        ];
+
+        let mut dividend = raw_address.clone();
+        let mut steps = Vec::with_capacity(levels.len());
+        for (level, divisor) in levels {
+            let (quotient, remainder) = dividend.div_rem(&BigUint::from(divisor));
+            let remainder = remainder.to_u32().unwrap_or(0);
+            steps.push(HierarchyStep {
+                level,
+                dividend: dividend.to_string(),
+                divisor,
+                quotient: quotient.to_string(),
+                remainder,
+                value: remainder + 1,
+            });
+            dividend = quotient;
+        }
+
+        let hierarchical = HierarchicalAddress::from_raw(raw_address, &self.geometry);
+        HierarchySplit {
+            raw_address: raw_address.to_string(),
+            raw_hex: raw_address.to_str_radix(16),
+            steps,
+            mandira: dividend.to_string(),
+            mandira_hex: hierarchical.mandira_hex(),
+            hierarchical: hierarchical.to_display_string(),
+        }
     }
 
-    /// Get the next page after the given location
-    pub fn next_page(&self, location: &Location) -> Page {
-        let next_location = location.next();
-        self.generate_page(&next_location)
+    /// Get the next page after the given location (None if at the last page)
+    pub fn next_page(&self, location: &Location) -> Option<Page> {
+        location
+            .next(self.page_count(), &self.geometry)
+            .map(|next_location| self.generate_page(&next_location))
     }
 
     /// Get the previous page before the given location (None if at first page)
     pub fn previous_page(&self, location: &Location) -> Option<Page> {
-        location.previous().map(|prev_location| self.generate_page(&prev_location))
+        location.previous(&self.geometry).map(|prev_location| self.generate_page(&prev_location))
     }
 
 
-    fn format_content(&self, indices: &[usize]) -> String {
-        let mut formatted = String::new();
-        for (i, &idx) in indices.iter().enumerate() {
-            if i > 0 && i % CLUSTERS_PER_LINE == 0 {
-                formatted.push('\n');
-            }
-            if let Some(cluster) = self.alphabet.get(idx) {
-                formatted.push_str(cluster);
-            }
+    /// Locate `cluster_count` clusters starting at cluster `position` of a page
+    pub fn match_span(&self, indices: &[usize], position: usize, cluster_count: usize) -> MatchSpan {
+        let per_line = self.geometry.clusters_per_line;
+        let end = (position + cluster_count).min(indices.len());
+        let position = position.min(end);
+
+        let measure = |range: &[usize]| {
+            range.iter().fold((0, 0), |(chars, bytes), &idx| {
+                let cluster = self.alphabet.get(idx).unwrap_or("");
+                (chars + cluster.chars().count(), bytes + cluster.len())
+            })
+        };
+        let (char_start, byte_start) = measure(&indices[..position]);
+        let (match_chars, match_bytes) = measure(&indices[position..end]);
+
+        // The fixed layout puts a newline before every line but the first
+        let start_breaks = position / per_line;
+        let end_breaks = if end > position { (end - 1) / per_line } else { start_breaks };
+
+        MatchSpan {
+            position,
+            cluster_count: end - position,
+            char_start,
+            char_end: char_start + match_chars,
+            byte_start,
+            byte_end: byte_start + match_bytes,
+            line: position / per_line,
+            column: position % per_line,
+            formatted_char_start: char_start + start_breaks,
+            formatted_char_end: char_start + match_chars + end_breaks,
         }
-        formatted
     }
 
-    /// Display mandira as Kannada text
-    pub fn mandira_as_kannada(&self, mandira: &BigUint) -> String {
-        let indices = self.bijection.biguint_to_indices(mandira.clone(), 399);
-        let start = indices.iter().position(|&x| x != 0).unwrap_or(0);
-        self.alphabet.indices_to_string(&indices[start..])
+    /// Display mandira as Kannada text (None for rooms outside the library)
+    pub fn mandira_as_kannada(&self, mandira: &BigUint) -> Option<String> {
+        if *mandira >= self.room_count() {
+            return None;
+        }
+        Some(Mandira::to_kannada(mandira, &self.alphabet))
     }
 }
 
 impl Default for LibraryOfBabel {
     fn default() -> Self { Self::new() }
-}
\ No newline at end of file
+}
+
+/// A page containing the query, with the token that produced it
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub token: BigUint,
+    pub result: SearchResult,
+}
+
+/// Iterator over distinct pages containing a query
+///
+/// Token `k` places the query at position `k mod P` (P = number of positions
+/// it fits at) and fills the other clusters with the base-N digits of `k div P`,
+/// least significant digit last, so token 0 is the page found by
+/// [`LibraryOfBabel::search`]. A page is only yielded for the leftmost place the
+/// query appears on it, so no page is yielded twice. Tokens depend only on the
+/// query and the library geometry: resuming from a token always continues the
+/// same sequence.
+pub struct Occurrences<'a> {
+    library: &'a LibraryOfBabel,
+    query: String,
+    query_indices: Vec<usize>,
+    positions: usize,
+    next: BigUint,
+    end: BigUint,
+}
+
+impl Occurrences<'_> {
+    /// Token of the next page to be yielded, or of a page skipped on the way to it
+    pub fn next_token(&self) -> &BigUint { &self.next }
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = Occurrence;
+
+    fn next(&mut self) -> Option<Occurrence> {
+        let page_length = self.library.geometry.clusters_per_page;
+        let query_len = self.query_indices.len();
+
+        // Position 0 is always leftmost, so at most P - 1 tokens are skipped in a row
+        while self.next < self.end {
+            let token = self.next.clone();
+            self.next += 1u32;
+
+            let (filler, position) = token.div_rem(&BigUint::from(self.positions));
+            let position = position.to_usize().unwrap_or(0);
+
+            let mut content =
+                self.library.bijection.biguint_to_indices(filler, page_length - query_len);
+            content.splice(position..position, self.query_indices.iter().copied());
+
+            let leftmost = content
+                .windows(query_len)
+                .position(|window| window == self.query_indices.as_slice());
+            if leftmost != Some(position) {
+                continue;
+            }
+
+            let result = self.library.search_result(&self.query, &content, position, query_len);
+            return Some(Occurrence { token, result });
+        }
+        None
+    }
+}
@@ -0,0 +1,88 @@
+//! Bijective Kannada names for rooms (ಮಂದಿರ)
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::alphabet::{GraphemeAlphabet, KannadaScript};
+
+/// Zero-width non-joiner, written between a halant-final cluster and a
+/// following consonant so the pair is not read back as one conjunct
+pub const MANDIRA_SEPARATOR: char = '\u{200C}';
+
+/// Kannada room naming scheme
+///
+/// Room `n` is written as the bijective base-N numeral of `n + 1`, where
+/// cluster index `i` stands for the digit `i + 1`. Every non-empty sequence
+/// of clusters names exactly one room and every room has exactly one name,
+/// so leading spaces are significant and no length limit applies.
+pub struct Mandira;
+
+impl Mandira {
+    /// Room number → cluster indices (most significant first)
+    pub fn to_indices(mandira: &BigUint, base: usize) -> Vec<usize> {
+        let base = BigUint::from(base);
+        let mut n = mandira + BigUint::one();
+        let mut digits = Vec::new();
+
+        while !n.is_zero() {
+            n -= BigUint::one();
+            let (q, r) = n.div_rem(&base);
+            digits.push(r.to_usize().unwrap_or(0));
+            n = q;
+        }
+
+        digits.reverse();
+        digits
+    }
+
+    /// Cluster indices → room number (None for an empty name)
+    pub fn from_indices(indices: &[usize], base: usize) -> Option<BigUint> {
+        if indices.is_empty() {
+            return None;
+        }
+
+        let base_big = BigUint::from(base);
+        let mut n = BigUint::zero();
+        for &idx in indices {
+            if idx >= base {
+                return None;
+            }
+            n = n * &base_big + BigUint::from(idx + 1);
+        }
+        Some(n - BigUint::one())
+    }
+
+    /// Display a room number as Kannada text
+    pub fn to_kannada(mandira: &BigUint, alphabet: &GraphemeAlphabet) -> String {
+        let halant = KannadaScript::halant();
+        let consonants = KannadaScript::consonants();
+
+        let mut name = String::new();
+        let mut prev_ends_in_halant = false;
+
+        for idx in Self::to_indices(mandira, alphabet.size()) {
+            let cluster = alphabet.get(idx).unwrap_or("");
+            let starts_with_consonant = cluster
+                .chars()
+                .next()
+                .is_some_and(|c| consonants.contains(&c));
+
+            if prev_ends_in_halant && starts_with_consonant {
+                name.push(MANDIRA_SEPARATOR);
+            }
+            name.push_str(cluster);
+            prev_ends_in_halant = cluster.ends_with(halant);
+        }
+        name
+    }
+
+    /// Parse a Kannada room name back into its room number
+    pub fn from_kannada(name: &str, alphabet: &GraphemeAlphabet) -> Option<BigUint> {
+        let mut indices = Vec::new();
+        for part in name.split(MANDIRA_SEPARATOR) {
+            indices.extend(alphabet.segment(part)?);
+        }
+        Self::from_indices(&indices, alphabet.size())
+    }
+}
@@ -0,0 +1,346 @@
+//! Cluster patterns for advanced search
+//!
+//! A pattern is a sequence of:
+//!
+//! - Kannada text, matched cluster by cluster
+//! - `?`, any single cluster
+//! - `<...>`, a class of clusters. Either a [`ClusterKind`] name such as
+//!   `<conjunct>`, or a template in which `C` stands for any consonant, `V` for
+//!   any independent vowel, `M` for any matra, `A` for ಂ or ಃ and `P` for any
+//!   punctuation, with other characters literal: `<Cಾ>` is any consonant + ಾ,
+//!   `<C್C>` any conjunct without a vowel sign.
+//! - `(a|b|c)`, one of several non-empty sub-patterns, which may differ in length
+//! - `{n}` after a cluster, `?`, class or group, exactly `n` (at least 1) repetitions
+//!
+//! `\` escapes the next character, so `\?` is a literal question mark.
+//!
+//! Patterns are limited to [`MAX_PATTERN_CHARS`] characters and groups to
+//! [`MAX_PATTERN_DEPTH`] levels of nesting, since they come from untrusted
+//! queries and the parser is recursive.
+
+use rand::Rng;
+
+use crate::alphabet::{ClusterKind, GraphemeAlphabet, KannadaScript};
+use crate::rng::random_index;
+
+/// Longest pattern accepted, in chars
+pub const MAX_PATTERN_CHARS: usize = 4096;
+
+/// Deepest nesting of `(...)` groups accepted
+pub const MAX_PATTERN_DEPTH: usize = 64;
+
+const SPECIAL: &[char] = &['?', '<', '>', '(', '|', ')', '{', '}', '\\'];
+
+/// Why a pattern couldn't be parsed or placed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The pattern stopped in the middle of a class, group or repetition
+    UnexpectedEnd,
+    /// A character that can't appear at this point (char offset)
+    Unexpected { position: usize, found: char },
+    /// Literal text that doesn't segment into clusters
+    InvalidText { position: usize },
+    /// A class that matches no cluster
+    EmptyClass { position: usize },
+    /// A `{n}` that isn't a count or follows nothing
+    BadRepetition { position: usize },
+    /// The pattern matches nothing, or its chosen text doesn't fit the placement
+    DoesNotFit,
+    /// The pattern is longer than [`MAX_PATTERN_CHARS`]
+    TooLong,
+    /// A group opens more than [`MAX_PATTERN_DEPTH`] levels deep (char offset)
+    TooDeep { position: usize },
+}
+
+impl std::fmt::Display for PatternError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternError::UnexpectedEnd => write!(f, "pattern ends unexpectedly"),
+            PatternError::Unexpected { position, found } => {
+                write!(f, "unexpected '{}' at {}", found, position)
+            }
+            PatternError::InvalidText { position } => {
+                write!(f, "text at {} is not in the alphabet", position)
+            }
+            PatternError::EmptyClass { position } => {
+                write!(f, "class at {} matches no cluster", position)
+            }
+            PatternError::BadRepetition { position } => {
+                write!(f, "bad repetition at {}", position)
+            }
+            PatternError::DoesNotFit => write!(f, "matching text does not fit on the page"),
+            PatternError::TooLong => {
+                write!(f, "pattern is longer than {} characters", MAX_PATTERN_CHARS)
+            }
+            PatternError::TooDeep { position } => {
+                write!(f, "group at {} is nested more than {} deep", position, MAX_PATTERN_DEPTH)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Literal(usize),
+    Any,
+    Class(Vec<usize>),
+    Alternation(Vec<Vec<Node>>),
+    Repeat(Box<Node>, usize),
+}
+
+/// A parsed cluster pattern
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    nodes: Vec<Node>,
+    alphabet_size: usize,
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str, alphabet: &GraphemeAlphabet) -> Result<Self, PatternError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        if chars.len() > MAX_PATTERN_CHARS {
+            return Err(PatternError::TooLong);
+        }
+        let mut parser = Parser { chars: &chars, pos: 0, depth: 0, alphabet };
+        let nodes = parser.sequence()?;
+
+        match parser.peek() {
+            None => Ok(Pattern { nodes, alphabet_size: alphabet.size() }),
+            Some(found) => Err(PatternError::Unexpected { position: parser.pos, found }),
+        }
+    }
+
+    /// The first matching text: first branch, first class member, and spaces for `?`
+    ///
+    /// None if the text would be longer than `max_len` clusters.
+    pub fn first_match(&self, max_len: usize) -> Option<Vec<usize>> {
+        self.choose(max_len, &mut |_| 0)
+    }
+
+    /// A matching text with each `?`, class and alternation chosen uniformly from `rng`
+    ///
+    /// Choices are independent, so texts reachable in more than one way are more likely.
+    pub fn random_match<R: Rng + ?Sized>(&self, max_len: usize, rng: &mut R) -> Option<Vec<usize>> {
+        self.choose(max_len, &mut |n| random_index(rng, n))
+    }
+
+    fn choose(&self, max_len: usize, pick: &mut dyn FnMut(usize) -> usize) -> Option<Vec<usize>> {
+        let mut out = Vec::new();
+        self.choose_nodes(&self.nodes, max_len, pick, &mut out)?;
+        Some(out)
+    }
+
+    fn choose_nodes(
+        &self,
+        nodes: &[Node],
+        max_len: usize,
+        pick: &mut dyn FnMut(usize) -> usize,
+        out: &mut Vec<usize>,
+    ) -> Option<()> {
+        for node in nodes {
+            self.choose_node(node, max_len, pick, out)?;
+        }
+        Some(())
+    }
+
+    fn choose_node(
+        &self,
+        node: &Node,
+        max_len: usize,
+        pick: &mut dyn FnMut(usize) -> usize,
+        out: &mut Vec<usize>,
+    ) -> Option<()> {
+        match node {
+            Node::Literal(index) => out.push(*index),
+            Node::Any => out.push(pick(self.alphabet_size)),
+            Node::Class(members) => out.push(members[pick(members.len())]),
+            Node::Alternation(branches) => {
+                let branch = &branches[pick(branches.len())];
+                self.choose_nodes(branch, max_len, pick, out)?;
+            }
+            Node::Repeat(inner, count) => {
+                for _ in 0..*count {
+                    self.choose_node(inner, max_len, pick, out)?;
+                }
+            }
+        }
+        (out.len() <= max_len).then_some(())
+    }
+}
+
+struct Parser<'a> {
+    chars: &'a [char],
+    pos: usize,
+    /// Groups currently open
+    depth: usize,
+    alphabet: &'a GraphemeAlphabet,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), PatternError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(found) => Err(PatternError::Unexpected { position: self.pos, found }),
+            None => Err(PatternError::UnexpectedEnd),
+        }
+    }
+
+    /// Items up to the end of the pattern, a `|` or a `)`
+    fn sequence(&mut self) -> Result<Vec<Node>, PatternError> {
+        let mut nodes = Vec::new();
+
+        while let Some(c) = self.peek() {
+            match c {
+                '|' | ')' => break,
+                '?' => {
+                    self.pos += 1;
+                    nodes.push(Node::Any);
+                }
+                '<' => nodes.push(self.class()?),
+                '(' => nodes.push(self.alternation()?),
+                '{' => {
+                    let position = self.pos;
+                    let count = self.repetition()?;
+                    let last = nodes.pop().ok_or(PatternError::BadRepetition { position })?;
+                    // Fold `x{a}{b}` into `x{a·b}` so chained repetitions don't nest
+                    let repeat = match last {
+                        Node::Repeat(inner, inner_count) => {
+                            let count = inner_count
+                                .checked_mul(count)
+                                .ok_or(PatternError::BadRepetition { position })?;
+                            Node::Repeat(inner, count)
+                        }
+                        last => Node::Repeat(Box::new(last), count),
+                    };
+                    nodes.push(repeat);
+                }
+                '>' | '}' => return Err(PatternError::Unexpected { position: self.pos, found: c }),
+                _ => nodes.extend(self.literal()?),
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// A run of literal (or escaped) characters, segmented into clusters
+    fn literal(&mut self) -> Result<Vec<Node>, PatternError> {
+        let position = self.pos;
+        let mut text = String::new();
+
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                let escaped = *self.chars.get(self.pos + 1).ok_or(PatternError::UnexpectedEnd)?;
+                text.push(escaped);
+                self.pos += 2;
+            } else if SPECIAL.contains(&c) {
+                break;
+            } else {
+                text.push(c);
+                self.pos += 1;
+            }
+        }
+
+        let indices = self
+            .alphabet
+            .segment(&text)
+            .ok_or(PatternError::InvalidText { position })?;
+        Ok(indices.into_iter().map(Node::Literal).collect())
+    }
+
+    fn class(&mut self) -> Result<Node, PatternError> {
+        let position = self.pos;
+        self.expect('<')?;
+
+        let mut template = Vec::new();
+        loop {
+            match self.peek() {
+                Some('>') => break,
+                Some(c) => template.push(c),
+                None => return Err(PatternError::UnexpectedEnd),
+            }
+            self.pos += 1;
+        }
+        self.expect('>')?;
+
+        let name: String = template.iter().collect();
+        let members = match ClusterKind::from_name(&name) {
+            Some(kind) => self.alphabet.indices_of_kind(kind),
+            None => (0..self.alphabet.size())
+                .filter(|&i| Self::fits_template(self.alphabet.get(i).unwrap_or(""), &template))
+                .collect(),
+        };
+
+        if members.is_empty() {
+            return Err(PatternError::EmptyClass { position });
+        }
+        Ok(Node::Class(members))
+    }
+
+    fn fits_template(cluster: &str, template: &[char]) -> bool {
+        cluster.chars().count() == template.len()
+            && cluster.chars().zip(template).all(|(c, &t)| match t {
+                'C' => KannadaScript::consonants().contains(&c),
+                'V' => KannadaScript::vowels().contains(&c),
+                'M' => KannadaScript::matras().contains(&c),
+                'A' => KannadaScript::modifiers().contains(&c),
+                'P' => KannadaScript::punctuation().contains(&c),
+                literal => c == literal,
+            })
+    }
+
+    fn alternation(&mut self) -> Result<Node, PatternError> {
+        if self.depth == MAX_PATTERN_DEPTH {
+            return Err(PatternError::TooDeep { position: self.pos });
+        }
+        self.expect('(')?;
+        self.depth += 1;
+
+        let mut branches = Vec::new();
+        loop {
+            // Every branch has at least one cluster, so repetitions always make progress
+            let branch = self.sequence()?;
+            if branch.is_empty() {
+                return match self.peek() {
+                    Some(found) => Err(PatternError::Unexpected { position: self.pos, found }),
+                    None => Err(PatternError::UnexpectedEnd),
+                };
+            }
+            branches.push(branch);
+
+            if self.peek() != Some('|') {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.expect(')')?;
+        self.depth -= 1;
+
+        Ok(Node::Alternation(branches))
+    }
+
+    fn repetition(&mut self) -> Result<usize, PatternError> {
+        let position = self.pos;
+        self.expect('{')?;
+
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        self.expect('}')?;
+
+        digits
+            .parse()
+            .ok()
+            .filter(|&count| count > 0)
+            .ok_or(PatternError::BadRepetition { position })
+    }
+}
@@ -0,0 +1,176 @@
+//! Exact odds of meeting a text by chance
+//!
+//! Every page is one of N^P strings, so "how likely is a random page to hold
+//! this text" is a counting problem. Pages holding it at the start are easy:
+//! the other P - L clusters are free, giving N^(P-L). Pages holding it
+//! anywhere are N^P minus the pages that avoid it, which are counted exactly
+//! by running the query's KMP automaton over the page one cluster at a time.
+
+use num_bigint::BigUint;
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive, Zero};
+use serde::{Serialize, Serializer};
+
+/// How many pages hold a text, and the odds of a random page holding it
+///
+/// Counts and ratios are exact; they serialize as decimal strings, ratios as
+/// `numerator/denominator` in lowest terms. The `log10_*` fields are the same
+/// probabilities as floating-point orders of magnitude, for display.
+#[derive(Debug, Clone, Serialize)]
+pub struct TextProbability {
+    pub query: String,
+    pub cluster_count: usize,
+    pub alphabet_size: usize,
+    pub page_length: usize,
+    #[serde(serialize_with = "decimal")]
+    pub total_pages: BigUint,
+    #[serde(serialize_with = "decimal")]
+    pub pages_with_text_at_start: BigUint,
+    #[serde(serialize_with = "decimal")]
+    pub pages_with_text_anywhere: BigUint,
+    #[serde(serialize_with = "ratio")]
+    pub probability_at_start: BigRational,
+    #[serde(serialize_with = "ratio")]
+    pub probability_anywhere: BigRational,
+    /// Mean number of random pages to browse before one holds the text at the
+    /// start (None if no page can)
+    #[serde(serialize_with = "optional_ratio")]
+    pub expected_pages_at_start: Option<BigRational>,
+    /// The same for the text anywhere on the page
+    #[serde(serialize_with = "optional_ratio")]
+    pub expected_pages_anywhere: Option<BigRational>,
+    pub log10_probability_at_start: f64,
+    pub log10_probability_anywhere: f64,
+}
+
+impl TextProbability {
+    /// Odds for `query`, already segmented into `indices`
+    pub fn new(query: &str, indices: &[usize], alphabet_size: usize, page_length: usize) -> Self {
+        let n = BigUint::from(alphabet_size);
+        let total_pages = n.pow(page_length as u32);
+        let cluster_count = indices.len();
+
+        let pages_with_text_at_start = match page_length.checked_sub(cluster_count) {
+            Some(free) => n.pow(free as u32),
+            None => BigUint::zero(),
+        };
+        let pages_with_text_anywhere =
+            &total_pages - count_pages_avoiding(indices, alphabet_size, page_length);
+
+        let probability = |pages: &BigUint| {
+            BigRational::new(pages.clone().into(), total_pages.clone().into())
+        };
+        let expected = |pages: &BigUint| {
+            (!pages.is_zero())
+                .then(|| BigRational::new(total_pages.clone().into(), pages.clone().into()))
+        };
+        let log10_probability = |pages: &BigUint| log10(pages) - log10(&total_pages);
+
+        TextProbability {
+            query: query.to_string(),
+            cluster_count,
+            alphabet_size,
+            page_length,
+            probability_at_start: probability(&pages_with_text_at_start),
+            probability_anywhere: probability(&pages_with_text_anywhere),
+            expected_pages_at_start: expected(&pages_with_text_at_start),
+            expected_pages_anywhere: expected(&pages_with_text_anywhere),
+            log10_probability_at_start: log10_probability(&pages_with_text_at_start),
+            log10_probability_anywhere: log10_probability(&pages_with_text_anywhere),
+            total_pages,
+            pages_with_text_at_start,
+            pages_with_text_anywhere,
+        }
+    }
+}
+
+/// Pages of `page_length` clusters that never contain `query`
+///
+/// State `s` of the automaton means the last `s` clusters read are the first
+/// `s` of the query. From each state only the clusters on its KMP fallback
+/// chain lead anywhere but state 0, so a step costs one multiplication per
+/// such cluster rather than one per letter of the alphabet.
+fn count_pages_avoiding(query: &[usize], alphabet_size: usize, page_length: usize) -> BigUint {
+    let length = query.len();
+    if length == 0 {
+        return BigUint::zero();
+    }
+
+    // fallback[s]: longest proper prefix of query[..s] that is also its suffix
+    let mut fallback = vec![0; length + 1];
+    for s in 2..=length {
+        let mut k = fallback[s - 1];
+        while k > 0 && query[k] != query[s - 1] {
+            k = fallback[k];
+        }
+        if query[k] == query[s - 1] {
+            k += 1;
+        }
+        fallback[s] = k;
+    }
+
+    // For each state, the states reached by the clusters that don't reset it
+    let transitions: Vec<Vec<(usize, usize)>> = (0..length)
+        .map(|state| {
+            let mut seen: Vec<(usize, usize)> = Vec::new();
+            let mut k = state;
+            loop {
+                if !seen.iter().any(|&(cluster, _)| cluster == query[k]) {
+                    seen.push((query[k], k + 1));
+                }
+                if k == 0 {
+                    break;
+                }
+                k = fallback[k];
+            }
+            seen
+        })
+        .collect();
+
+    let mut counts = vec![BigUint::zero(); length];
+    counts[0] = BigUint::one();
+    for _ in 0..page_length {
+        let mut next = vec![BigUint::zero(); length];
+        for (state, count) in counts.iter().enumerate() {
+            if count.is_zero() {
+                continue;
+            }
+            let moves = &transitions[state];
+            next[0] += count * (alphabet_size - moves.len());
+            for &(_, target) in moves {
+                // Reaching `length` means the query was read: not an avoiding page
+                if target < length {
+                    next[target] += count;
+                }
+            }
+        }
+        counts = next;
+    }
+    counts.into_iter().sum()
+}
+
+/// log10 of a big number from its top 64 bits (-inf for zero)
+fn log10(value: &BigUint) -> f64 {
+    let bits = value.bits();
+    let shift = bits.saturating_sub(64);
+    let top = (value >> shift).to_f64().unwrap_or(0.0);
+    top.log10() + shift as f64 * std::f64::consts::LOG10_2
+}
+
+fn decimal<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn ratio<S: Serializer>(value: &BigRational, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&format_args!("{}/{}", value.numer(), value.denom()))
+}
+
+fn optional_ratio<S: Serializer>(
+    value: &Option<BigRational>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => ratio(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
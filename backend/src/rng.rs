@@ -0,0 +1,65 @@
+//! Randomness for the library
+//!
+//! Every randomized operation takes an explicit generator, so results can be
+//! reproduced from a seed. The convenience methods that don't take one draw OS
+//! entropy through [`entropy_rng`] and nowhere else.
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Generator used throughout the library
+///
+/// ChaCha20 gives the same stream for the same seed on every platform,
+/// including WASM, so a seed can be shared between the server and the browser.
+pub type LibraryRng = ChaCha20Rng;
+
+/// Reproducible generator for "run with seed X"
+pub fn seeded_rng(seed: u64) -> LibraryRng {
+    LibraryRng::seed_from_u64(seed)
+}
+
+/// Generator seeded from OS entropy (`crypto.getRandomValues` under WASM)
+pub fn entropy_rng() -> LibraryRng {
+    LibraryRng::from_entropy()
+}
+
+/// [`seeded_rng`] when a seed is given, otherwise [`entropy_rng`]
+pub fn rng_from_seed(seed: Option<u64>) -> LibraryRng {
+    match seed {
+        Some(seed) => seeded_rng(seed),
+        None => entropy_rng(),
+    }
+}
+
+/// Uniform index in `[0, bound)`, for `bound > 0`
+///
+/// Samples in u64 so 32-bit (WASM) and 64-bit builds agree for the same seed.
+pub fn random_index<R: Rng + ?Sized>(rng: &mut R, bound: usize) -> usize {
+    rng.gen_range(0..bound as u64) as usize
+}
+
+/// Uniform integer in `[0, bound)` by rejection sampling
+///
+/// Draws just enough random bytes to cover `bound`, masks off the excess high
+/// bits and retries when the result is too large (fewer than two draws on average).
+pub fn random_below<R: Rng + ?Sized>(rng: &mut R, bound: &BigUint) -> BigUint {
+    if bound.is_zero() {
+        return BigUint::zero();
+    }
+
+    let bits = bound.bits();
+    let mut bytes = vec![0u8; bits.div_ceil(8) as usize];
+    let excess_bits = bytes.len() as u64 * 8 - bits;
+    let top_mask = 0xffu8 >> excess_bits;
+
+    loop {
+        rng.fill(bytes.as_mut_slice());
+        bytes[0] &= top_mask;
+        let candidate = BigUint::from_bytes_be(&bytes);
+        if candidate < *bound {
+            return candidate;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Coherence scoring: how much a page looks like real Kannada
+//!
+//! A [`CoherenceModel`] is built offline from a word list, one word per line,
+//! optionally followed by whitespace and a frequency count (default 1). Lines
+//! starting with `#` and words outside the alphabet are skipped. The model
+//! knows the words themselves and the cluster bigrams inside them, with the
+//! space before and after each word.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::alphabet::GraphemeAlphabet;
+use crate::types::Location;
+
+const SPACE: usize = 0;
+
+/// Shortest dictionary word counted on a page; single clusters turn up by
+/// chance several times on every random page
+pub const MIN_WORD_CLUSTERS: usize = 2;
+
+/// Words and cluster bigram counts from a word list
+pub struct CoherenceModel {
+    words: HashSet<Vec<usize>>,
+    longest_word: usize,
+    bigrams: HashMap<(usize, usize), u64>,
+    followers: HashMap<usize, u64>,
+    alphabet_size: usize,
+}
+
+/// A dictionary word found on a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WordMatch {
+    pub position: usize,
+    pub cluster_count: usize,
+    pub text: String,
+}
+
+/// How plausible a page is as Kannada
+///
+/// `bits_per_cluster` is the mean log2 ratio of the bigram model's probability
+/// for each cluster to the uniform 1/N: around zero or below for random pages,
+/// clearly positive for real text. Pages rank by dictionary clusters first,
+/// then by bits per cluster.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageScore {
+    pub word_clusters: usize,
+    pub bits_per_cluster: f64,
+    pub words: Vec<WordMatch>,
+}
+
+impl PageScore {
+    /// Greater is more coherent
+    pub fn compare(&self, other: &PageScore) -> Ordering {
+        self.word_clusters
+            .cmp(&other.word_clusters)
+            .then(self.bits_per_cluster.total_cmp(&other.bits_per_cluster))
+    }
+}
+
+/// A sampled page and its score
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoredPage {
+    pub location: Location,
+    pub content: String,
+    pub score: PageScore,
+}
+
+impl CoherenceModel {
+    /// Build a model from the text of a word list
+    pub fn from_word_list(list: &str, alphabet: &GraphemeAlphabet) -> Self {
+        let mut model = CoherenceModel {
+            words: HashSet::new(),
+            longest_word: 0,
+            bigrams: HashMap::new(),
+            followers: HashMap::new(),
+            alphabet_size: alphabet.size(),
+        };
+
+        for line in list.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next().and_then(|word| alphabet.segment(word)) else {
+                continue;
+            };
+            let count = fields.next().and_then(|count| count.parse().ok()).unwrap_or(1);
+            model.add_word(word, count);
+        }
+        model
+    }
+
+    /// Read a word list file
+    pub fn load(path: impl AsRef<Path>, alphabet: &GraphemeAlphabet) -> io::Result<Self> {
+        Ok(Self::from_word_list(&fs::read_to_string(path)?, alphabet))
+    }
+
+    fn add_word(&mut self, word: Vec<usize>, count: u64) {
+        let mut padded = Vec::with_capacity(word.len() + 2);
+        padded.push(SPACE);
+        padded.extend_from_slice(&word);
+        padded.push(SPACE);
+
+        for pair in padded.windows(2) {
+            *self.bigrams.entry((pair[0], pair[1])).or_insert(0) += count;
+            *self.followers.entry(pair[0]).or_insert(0) += count;
+        }
+        if word.len() >= MIN_WORD_CLUSTERS {
+            self.longest_word = self.longest_word.max(word.len());
+            self.words.insert(word);
+        }
+    }
+
+    /// Number of distinct words of at least [`MIN_WORD_CLUSTERS`] clusters
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Score a page given as cluster indices
+    pub fn score(&self, indices: &[usize], alphabet: &GraphemeAlphabet) -> PageScore {
+        let words = self.find_words(indices, alphabet);
+        let word_clusters = words.iter().map(|word| word.cluster_count).sum();
+
+        PageScore { word_clusters, bits_per_cluster: self.bits_per_cluster(indices), words }
+    }
+
+    /// Longest dictionary words, left to right, without overlaps
+    fn find_words(&self, indices: &[usize], alphabet: &GraphemeAlphabet) -> Vec<WordMatch> {
+        let mut words = Vec::new();
+        let mut position = 0;
+
+        while position < indices.len() {
+            let longest = self.longest_word.min(indices.len() - position);
+            let found = (MIN_WORD_CLUSTERS..=longest)
+                .rev()
+                .find(|&len| self.words.contains(&indices[position..position + len]));
+
+            match found {
+                Some(cluster_count) => {
+                    let run = &indices[position..position + cluster_count];
+                    words.push(WordMatch {
+                        position,
+                        cluster_count,
+                        text: alphabet.indices_to_string(run),
+                    });
+                    position += cluster_count;
+                }
+                None => position += 1,
+            }
+        }
+        words
+    }
+
+    /// Mean log2 of P(cluster | previous) · N, with add-one smoothing
+    fn bits_per_cluster(&self, indices: &[usize]) -> f64 {
+        if indices.len() < 2 {
+            return 0.0;
+        }
+        let n = self.alphabet_size as f64;
+        let total: f64 = indices
+            .windows(2)
+            .map(|pair| {
+                let seen = self.bigrams.get(&(pair[0], pair[1])).copied().unwrap_or(0) as f64;
+                let after = self.followers.get(&pair[0]).copied().unwrap_or(0) as f64;
+                ((seen + 1.0) * n / (after + n)).log2()
+            })
+            .sum();
+        total / (indices.len() - 1) as f64
+    }
+}
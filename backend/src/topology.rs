@@ -0,0 +1,136 @@
+//! Hexagonal gallery topology: which rooms connect to which
+//!
+//! In Borges' library every hexagonal gallery has a spiral staircase leading
+//! up and down, and two free sides opening onto corridors to further galleries.
+//! Each of these is modelled as a fixed step through the room numbers (mod the
+//! room count), so moving in a direction and then in its opposite always comes
+//! back to the same room. The steps are coprime to the room count, so following
+//! the staircase (or a corridor) for long enough visits every room.
+
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+/// 10^18 / φ and 10^18 / φ², for steps that look unrelated to the room number
+const INV_PHI_NUM: u128 = 1_000_000_000_000_000_000;
+const PHI_DEN: u128 = 1_618_033_988_749_894_848;
+const PHI_SQUARED_DEN: u128 = 2_618_033_988_749_894_848;
+
+/// A way out of a gallery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [Direction::Up, Direction::Down, Direction::Left, Direction::Right]
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            "left" => Some(Direction::Left),
+            "right" => Some(Direction::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Rooms adjacent to a gallery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbours {
+    pub up: BigUint,
+    pub down: BigUint,
+    pub left: BigUint,
+    pub right: BigUint,
+}
+
+/// Adjacency between the rooms of a library
+pub struct GalleryTopology {
+    room_count: BigUint,
+    stair_step: BigUint,
+    corridor_step: BigUint,
+}
+
+impl GalleryTopology {
+    pub fn new(room_count: BigUint) -> Self {
+        let stair_step = Self::coprime_step(&room_count, PHI_DEN);
+        let mut corridor_step = Self::coprime_step(&room_count, PHI_SQUARED_DEN);
+
+        // Keep corridors distinct from the staircase where the library is big
+        // enough. Six rooms have only ±1 as coprime steps, so give up once the
+        // candidates wrap around.
+        let down_step = (&room_count - &stair_step % &room_count) % &room_count;
+        let first_candidate = corridor_step.clone();
+        while room_count > BigUint::from(4u32)
+            && (corridor_step == stair_step || corridor_step == down_step)
+        {
+            corridor_step = Self::coprime_step_from(&room_count, corridor_step + 1u32);
+            if corridor_step == first_candidate {
+                break;
+            }
+        }
+
+        GalleryTopology { room_count, stair_step, corridor_step }
+    }
+
+    /// First step at or above `room_count / ratio` that is coprime to `room_count`
+    fn coprime_step(room_count: &BigUint, ratio_den: u128) -> BigUint {
+        let target = room_count * INV_PHI_NUM / ratio_den;
+        Self::coprime_step_from(room_count, target)
+    }
+
+    fn coprime_step_from(room_count: &BigUint, start: BigUint) -> BigUint {
+        if room_count.is_zero() {
+            return BigUint::zero();
+        }
+        let mut step = start.max(BigUint::one());
+        while step.gcd(room_count) != BigUint::one() {
+            step += BigUint::one();
+        }
+        step % room_count
+    }
+
+    pub fn room_count(&self) -> &BigUint { &self.room_count }
+
+    /// The room reached by leaving `mandira` in `direction`
+    pub fn neighbour(&self, mandira: &BigUint, direction: Direction) -> BigUint {
+        if self.room_count.is_zero() {
+            return mandira.clone();
+        }
+        let n = &self.room_count;
+        let m = mandira % n;
+        match direction {
+            Direction::Up => (m + &self.stair_step) % n,
+            Direction::Down => (m + n - &self.stair_step) % n,
+            Direction::Left => (m + n - &self.corridor_step) % n,
+            Direction::Right => (m + &self.corridor_step) % n,
+        }
+    }
+
+    /// All rooms adjacent to `mandira`
+    pub fn neighbours(&self, mandira: &BigUint) -> Neighbours {
+        Neighbours {
+            up: self.neighbour(mandira, Direction::Up),
+            down: self.neighbour(mandira, Direction::Down),
+            left: self.neighbour(mandira, Direction::Left),
+            right: self.neighbour(mandira, Direction::Right),
+        }
+    }
+}
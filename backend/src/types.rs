@@ -1,51 +1,62 @@
 //! Core types: addresses, locations, pages, and search results
 
-use num_bigint::BigUint;
-use num_traits::{Zero, One, ToPrimitive};
+use num_bigint::{BigInt, BigUint};
+use num_traits::{Zero, One, Signed, ToPrimitive};
 use num_integer::Integer;
 use serde::{Deserialize, Serialize};
 
-use crate::constants::*;
+use crate::geometry::LibraryGeometry;
 
 // ============================================================================
 // Address Types
 // ============================================================================
 
 /// Hierarchical address breakdown
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The levels are `u32` to match [`LibraryGeometry`], whose counts can exceed
+/// the Borges library's; ranges in the comments are for the default geometry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HierarchicalAddress {
     pub mandira: BigUint,  // Room
-    pub gode: u8,          // Wall (1-4)
-    pub patti: u8,         // Shelf (1-5)
-    pub pustaka: u8,       // Book (1-32)
-    pub puta: u16,         // Page (1-410)
+    pub gode: u32,         // Wall (1-4)
+    pub patti: u32,        // Shelf (1-5)
+    pub pustaka: u32,      // Book (1-32)
+    pub puta: u32,         // Page (1-410)
 }
 
 impl HierarchicalAddress {
-    pub fn from_raw(raw: &BigUint) -> Self {
-        let (rest, puta) = raw.div_rem(&BigUint::from(PAGES_PER_BOOK));
-        let (rest, pustaka) = rest.div_rem(&BigUint::from(BOOKS_PER_SHELF));
-        let (rest, patti) = rest.div_rem(&BigUint::from(SHELVES_PER_WALL));
-        let (mandira, gode) = rest.div_rem(&BigUint::from(WALLS_PER_ROOM));
+    pub fn from_raw(raw: &BigUint, geometry: &LibraryGeometry) -> Self {
+        let (rest, puta) = raw.div_rem(&BigUint::from(geometry.pages_per_book));
+        let (rest, pustaka) = rest.div_rem(&BigUint::from(geometry.books_per_shelf));
+        let (rest, patti) = rest.div_rem(&BigUint::from(geometry.shelves_per_wall));
+        let (mandira, gode) = rest.div_rem(&BigUint::from(geometry.walls_per_room));
 
         HierarchicalAddress {
             mandira,
-            gode: gode.to_u8().unwrap_or(0) + 1,
-            patti: patti.to_u8().unwrap_or(0) + 1,
-            pustaka: pustaka.to_u8().unwrap_or(0) + 1,
-            puta: puta.to_u16().unwrap_or(0) + 1,
+            gode: gode.to_u32().unwrap_or(0) + 1,
+            patti: patti.to_u32().unwrap_or(0) + 1,
+            pustaka: pustaka.to_u32().unwrap_or(0) + 1,
+            puta: puta.to_u32().unwrap_or(0) + 1,
         }
     }
 
-    pub fn to_raw(&self) -> BigUint {
+    pub fn to_raw(&self, geometry: &LibraryGeometry) -> BigUint {
         let mut raw = self.mandira.clone();
-        raw = raw * WALLS_PER_ROOM + (self.gode - 1) as u32;
-        raw = raw * SHELVES_PER_WALL + (self.patti - 1) as u32;
-        raw = raw * BOOKS_PER_SHELF + (self.pustaka - 1) as u32;
-        raw = raw * PAGES_PER_BOOK + (self.puta - 1) as u32;
+        raw = raw * geometry.walls_per_room + (self.gode - 1);
+        raw = raw * geometry.shelves_per_wall + (self.patti - 1);
+        raw = raw * geometry.books_per_shelf + (self.pustaka - 1);
+        raw = raw * geometry.pages_per_book + (self.puta - 1);
         raw
     }
 
+    /// Check that gode, patti, pustaka and puta are within the geometry (1-based)
+    pub fn is_valid(&self, geometry: &LibraryGeometry) -> bool {
+        (1..=geometry.walls_per_room).contains(&self.gode)
+            && (1..=geometry.shelves_per_wall).contains(&self.patti)
+            && (1..=geometry.books_per_shelf).contains(&self.pustaka)
+            && (1..=geometry.pages_per_book).contains(&self.puta)
+    }
+
     pub fn mandira_hex(&self) -> String {
         if self.mandira.is_zero() {
             "0".to_string()
@@ -61,14 +72,15 @@ impl HierarchicalAddress {
         )
     }
 
-    pub fn from_display_string(s: &str) -> Option<Self> {
+    /// Parse `mandira.gode.patti.pustaka.puta` (None if a level is outside the geometry)
+    pub fn from_display_string(s: &str, geometry: &LibraryGeometry) -> Option<Self> {
         let parts: Vec<&str> = s.rsplitn(5, '.').collect();
         if parts.len() != 5 { return None; }
 
-        let puta = parts[0].parse::<u16>().ok()?;
-        let pustaka = parts[1].parse::<u8>().ok()?;
-        let patti = parts[2].parse::<u8>().ok()?;
-        let gode = parts[3].parse::<u8>().ok()?;
+        let puta = parts[0].parse::<u32>().ok()?;
+        let pustaka = parts[1].parse::<u32>().ok()?;
+        let patti = parts[2].parse::<u32>().ok()?;
+        let gode = parts[3].parse::<u32>().ok()?;
         let mandira_hex = parts[4];
 
         let mandira = if mandira_hex == "0" {
@@ -77,123 +89,201 @@ impl HierarchicalAddress {
             BigUint::parse_bytes(mandira_hex.as_bytes(), 16)?
         };
 
-        Some(HierarchicalAddress { mandira, gode, patti, pustaka, puta })
+        let h = HierarchicalAddress { mandira, gode, patti, pustaka, puta };
+        h.is_valid(geometry).then_some(h)
     }
 
     /// Get the next page in sequence
     /// Overflows: puta → pustaka → patti → gode → mandira
-    pub fn next(&self) -> Self {
-        let mut mandira = self.mandira.clone();
-        let mut gode = self.gode;
-        let mut patti = self.patti;
-        let mut pustaka = self.pustaka;
-        let mut puta = self.puta;
-
-        puta += 1;
-        if puta > PAGES_PER_BOOK as u16 {
-            puta = 1;
-            pustaka += 1;
-            if pustaka > BOOKS_PER_SHELF as u8 {
-                pustaka = 1;
-                patti += 1;
-                if patti > SHELVES_PER_WALL as u8 {
-                    patti = 1;
-                    gode += 1;
-                    if gode > WALLS_PER_ROOM as u8 {
-                        gode = 1;
-                        mandira += BigUint::one();
-                    }
-                }
-            }
-        }
-
-        HierarchicalAddress { mandira, gode, patti, pustaka, puta }
+    pub fn next(&self, geometry: &LibraryGeometry) -> Self {
+        Self::from_raw(&(self.to_raw(geometry) + BigUint::one()), geometry)
     }
 
     /// Get the previous page in sequence
     /// Underflows: puta → pustaka → patti → gode → mandira
-    pub fn previous(&self) -> Option<Self> {
-        let mut mandira = self.mandira.clone();
-        let mut gode = self.gode;
-        let mut patti = self.patti;
-        let mut pustaka = self.pustaka;
-        let mut puta = self.puta;
-
-        if puta > 1 {
-            puta -= 1;
-        } else {
-            puta = PAGES_PER_BOOK as u16;
-            if pustaka > 1 {
-                pustaka -= 1;
-            } else {
-                pustaka = BOOKS_PER_SHELF as u8;
-                if patti > 1 {
-                    patti -= 1;
-                } else {
-                    patti = SHELVES_PER_WALL as u8;
-                    if gode > 1 {
-                        gode -= 1;
-                    } else {
-                        gode = WALLS_PER_ROOM as u8;
-                        if mandira.is_zero() {
-                            // Already at the very first page
-                            return None;
-                        }
-                        mandira -= BigUint::one();
-                    }
-                }
-            }
+    pub fn previous(&self, geometry: &LibraryGeometry) -> Option<Self> {
+        let raw = self.to_raw(geometry);
+        if raw.is_zero() {
+            // Already at the very first page
+            return None;
         }
+        Some(Self::from_raw(&(raw - BigUint::one()), geometry))
+    }
+}
+
+/// A level of the library hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Puta,
+    Pustaka,
+    Patti,
+    Gode,
+    Mandira,
+}
+
+impl Level {
+    /// Number of pages spanned by one unit of this level
+    pub fn pages(&self, geometry: &LibraryGeometry) -> BigUint {
+        let pages = match self {
+            Level::Puta => 1,
+            Level::Pustaka => geometry.pages_per_book as u64,
+            Level::Patti => geometry.pages_per_shelf(),
+            Level::Gode => geometry.pages_per_wall(),
+            Level::Mandira => geometry.pages_per_room(),
+        };
+        BigUint::from(pages)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "puta" | "page" => Some(Level::Puta),
+            "pustaka" | "book" => Some(Level::Pustaka),
+            "patti" | "shelf" => Some(Level::Patti),
+            "gode" | "wall" => Some(Level::Gode),
+            "mandira" | "room" => Some(Level::Mandira),
+            _ => None,
+        }
+    }
+}
+
+/// Signed distance between two addresses
+///
+/// `pages` is the exact signed page count. The same distance is also split into
+/// whole rooms, walls, shelves, books and pages, each carrying the sign of `pages`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressDistance {
+    pub pages: BigInt,
+    pub mandira: BigInt,
+    pub gode: i64,
+    pub patti: i64,
+    pub pustaka: i64,
+    pub puta: i64,
+}
+
+impl AddressDistance {
+    pub fn from_pages(pages: BigInt, geometry: &LibraryGeometry) -> Self {
+        let sign = if pages.is_negative() { -1 } else { 1 };
+        let split = HierarchicalAddress::from_raw(pages.magnitude(), geometry);
+
+        let signed = |n: u32| (n as i64 - 1) * sign;
+        AddressDistance {
+            mandira: BigInt::from(split.mandira) * sign,
+            gode: signed(split.gode),
+            patti: signed(split.patti),
+            pustaka: signed(split.pustaka),
+            puta: signed(split.puta),
+            pages,
+        }
+    }
 
-        Some(HierarchicalAddress { mandira, gode, patti, pustaka, puta })
+    pub fn is_zero(&self) -> bool {
+        self.pages.is_zero()
     }
 }
 
 /// Complete location with raw and hierarchical forms
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Location {
     pub raw_hex: String,
     pub hierarchical: HierarchicalAddress,
 }
 
 impl Location {
-    pub fn from_raw_address(raw: &BigUint) -> Self {
-        let raw_hex = if raw.is_zero() {
+    pub fn from_raw_address(raw: &BigUint, geometry: &LibraryGeometry) -> Self {
+        let hierarchical = HierarchicalAddress::from_raw(raw, geometry);
+        Location { raw_hex: Self::hex(raw), hierarchical }
+    }
+
+    fn hex(raw: &BigUint) -> String {
+        if raw.is_zero() {
             "0".to_string()
         } else {
             raw.to_str_radix(16)
-        };
-        let hierarchical = HierarchicalAddress::from_raw(raw);
-        Location { raw_hex, hierarchical }
+        }
     }
 
-    pub fn from_hex(hex: &str) -> Option<Self> {
+    pub fn from_hex(hex: &str, geometry: &LibraryGeometry) -> Option<Self> {
         let raw = if hex == "0" {
             BigUint::zero()
         } else {
             BigUint::parse_bytes(hex.as_bytes(), 16)?
         };
-        Some(Self::from_raw_address(&raw))
+        Some(Self::from_raw_address(&raw, geometry))
     }
 
-    pub fn from_hierarchical(h: HierarchicalAddress) -> Self {
-        let raw = h.to_raw();
-        Self::from_raw_address(&raw)
+    /// Build a location from a hierarchical address (None if it is out of range)
+    pub fn from_hierarchical(h: HierarchicalAddress, geometry: &LibraryGeometry) -> Option<Self> {
+        if !h.is_valid(geometry) {
+            return None;
+        }
+        let raw_hex = Self::hex(&h.to_raw(geometry));
+        Some(Location { raw_hex, hierarchical: h })
     }
 
     pub fn to_raw(&self) -> BigUint {
-        self.hierarchical.to_raw()
+        BigUint::parse_bytes(self.raw_hex.as_bytes(), 16).unwrap_or_default()
     }
 
-    /// Get the next page location
-    pub fn next(&self) -> Self {
-        let next_h = self.hierarchical.next();
-        Self::from_hierarchical(next_h)
+    /// Get the next page location (None if at the last of `page_count` pages)
+    pub fn next(&self, page_count: &BigUint, geometry: &LibraryGeometry) -> Option<Self> {
+        self.advance(&BigUint::one(), page_count, geometry)
     }
 
     /// Get the previous page location (None if at first page)
-    pub fn previous(&self) -> Option<Self> {
-        self.hierarchical.previous().map(Self::from_hierarchical)
+    pub fn previous(&self, geometry: &LibraryGeometry) -> Option<Self> {
+        self.retreat(&BigUint::one(), geometry)
+    }
+
+    /// Move forward by any number of pages (None if that would pass the
+    /// last of `page_count` pages)
+    pub fn advance(
+        &self,
+        pages: &BigUint,
+        page_count: &BigUint,
+        geometry: &LibraryGeometry,
+    ) -> Option<Self> {
+        let raw = self.to_raw() + pages;
+        (raw < *page_count).then(|| Self::from_raw_address(&raw, geometry))
+    }
+
+    /// Move back by any number of pages (None if that would pass the first page)
+    pub fn retreat(&self, pages: &BigUint, geometry: &LibraryGeometry) -> Option<Self> {
+        let raw = self.to_raw();
+        if *pages > raw {
+            return None;
+        }
+        Some(Self::from_raw_address(&(raw - pages), geometry))
+    }
+
+    /// Move by a signed number of pages (None if that would pass the first
+    /// page or the last of `page_count` pages)
+    pub fn offset(
+        &self,
+        pages: &BigInt,
+        page_count: &BigUint,
+        geometry: &LibraryGeometry,
+    ) -> Option<Self> {
+        let raw = (BigInt::from(self.to_raw()) + pages).to_biguint()?;
+        (raw < *page_count).then(|| Self::from_raw_address(&raw, geometry))
+    }
+
+    /// Jump by a signed number of whole books, shelves, walls or rooms,
+    /// keeping the position within them (None if that leaves the library)
+    pub fn jump(
+        &self,
+        level: Level,
+        count: &BigInt,
+        page_count: &BigUint,
+        geometry: &LibraryGeometry,
+    ) -> Option<Self> {
+        self.offset(&(count * BigInt::from(level.pages(geometry))), page_count, geometry)
+    }
+
+    /// Signed distance from this location to `other`
+    pub fn distance_to(&self, other: &Location, geometry: &LibraryGeometry) -> AddressDistance {
+        let pages = BigInt::from(other.to_raw()) - BigInt::from(self.to_raw());
+        AddressDistance::from_pages(pages, geometry)
     }
 }
 
@@ -208,6 +298,110 @@ pub struct Page {
     pub content: String,
     pub formatted_content: String,
     pub cluster_indices: Vec<usize>,
+    /// Where each line of `formatted_content` comes from
+    pub lines: Vec<LineSpan>,
+}
+
+/// One line of a formatted page
+///
+/// Clusters `start..end` are on this line, and `char_start..char_end` indexes
+/// its text (in chars) in `Page::formatted_content`. Wrapped layouts leave the
+/// spaces they break at out of the text, so the last clusters of a line may
+/// be spaces with no text; justified lines also carry padding spaces that
+/// belong to no cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineSpan {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+/// A whole book (ಪುಸ್ತಕ), starting at its first page
+#[derive(Debug, Clone, Serialize)]
+pub struct Book {
+    pub location: Location,
+    pub title: String,
+    pub pages: Vec<Page>,
+}
+
+/// A book as seen on its shelf
+#[derive(Debug, Clone, Serialize)]
+pub struct BookSpine {
+    pub pustaka: u32,
+    pub location: Location,
+    pub title: String,
+}
+
+/// Where a run of clusters sits on a page
+///
+/// Char and byte offsets index into `Page::content`; the `formatted_*` offsets
+/// index (in chars) into `Page::formatted_content` in the fixed layout, which
+/// has line breaks.
+/// Lines and columns count from 0, with columns measured in clusters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchSpan {
+    pub position: usize,
+    pub cluster_count: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub formatted_char_start: usize,
+    pub formatted_char_end: usize,
+}
+
+/// Where to put a query on a page
+///
+/// Lines and columns count from 0; columns are measured in clusters. Text that
+/// is longer than the rest of its line carries on into the following lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Placement {
+    /// Starting at a cluster offset
+    Position { position: usize },
+    /// Starting at a line and column
+    LineColumn { line: usize, column: usize },
+    /// Centred on a line
+    LineCentre { line: usize },
+    /// Right-aligned on a line
+    LineEnd { line: usize },
+    /// Right-aligned at the end of the page
+    PageEnd,
+}
+
+impl Placement {
+    /// Cluster offset for text of `cluster_count` clusters (None if it would not fit)
+    pub fn resolve(&self, cluster_count: usize, geometry: &LibraryGeometry) -> Option<usize> {
+        let page = geometry.clusters_per_page;
+        let per_line = geometry.clusters_per_line;
+        let line_bounds = |line: usize| {
+            let start = line.checked_mul(per_line).filter(|&start| start < page)?;
+            Some((start, (page - start).min(per_line)))
+        };
+
+        let position = match *self {
+            Placement::Position { position } => position,
+            Placement::LineColumn { line, column } => {
+                let (start, width) = line_bounds(line)?;
+                if column >= width { return None; }
+                start + column
+            }
+            Placement::LineCentre { line } => {
+                let (start, width) = line_bounds(line)?;
+                start + width.saturating_sub(cluster_count) / 2
+            }
+            Placement::LineEnd { line } => {
+                let (start, width) = line_bounds(line)?;
+                (start + width).checked_sub(cluster_count)?
+            }
+            Placement::PageEnd => page.checked_sub(cluster_count)?,
+        };
+
+        (position.checked_add(cluster_count)? <= page).then_some(position)
+    }
 }
 
 /// Result of a search query
@@ -216,4 +410,116 @@ pub struct SearchResult {
     pub query: String,
     pub location: Location,
     pub cluster_count: usize,
-}
\ No newline at end of file
+    pub span: MatchSpan,
+}
+
+/// One cluster of expected text against the page cluster it was aligned with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClusterComparison {
+    pub expected: String,
+    pub actual: String,
+    pub equal: bool,
+}
+
+/// Cluster-level diff of expected text at its best alignment on a page
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyDiff {
+    /// Page cluster where the expected text lines up best
+    pub position: usize,
+    pub matching_clusters: usize,
+    pub clusters: Vec<ClusterComparison>,
+}
+
+/// The arithmetic behind a page, for checking without this library
+///
+/// `content` is the page's cluster indices read as a base-`alphabet_size`
+/// number (first cluster most significant), and `address` is
+/// `content × multiplier mod modulus`. Numbers are decimal strings.
+#[derive(Debug, Clone, Serialize)]
+pub struct BijectionProof {
+    pub alphabet_size: usize,
+    pub cluster_indices: Vec<usize>,
+    pub content: String,
+    pub multiplier: String,
+    pub modulus: String,
+    pub product: String,
+    pub address: String,
+    pub address_hex: String,
+}
+
+/// Where and whether text appears on a page
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub at_start: bool,
+    pub found: bool,
+    /// Every cluster-aligned occurrence, left to right
+    pub occurrences: Vec<MatchSpan>,
+    /// Present when the text doesn't occur on the page
+    pub diff: Option<VerifyDiff>,
+    pub proof: BijectionProof,
+}
+
+/// Address levels a constrained search must land on; `None` leaves a level free
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressConstraint {
+    pub mandira: Option<BigUint>,
+    pub gode: Option<u32>,
+    pub patti: Option<u32>,
+    pub pustaka: Option<u32>,
+    pub puta: Option<u32>,
+}
+
+impl AddressConstraint {
+    /// Fixed levels must be within the geometry (1-based)
+    pub fn is_valid(&self, geometry: &LibraryGeometry) -> bool {
+        let within = |value: Option<u32>, count: u32| value.is_none_or(|v| (1..=count).contains(&v));
+        within(self.gode, geometry.walls_per_room)
+            && within(self.patti, geometry.shelves_per_wall)
+            && within(self.pustaka, geometry.books_per_shelf)
+            && within(self.puta, geometry.pages_per_book)
+    }
+}
+
+/// Outcome of a constrained search
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ConstrainedSearch {
+    Found(SearchResult),
+    /// No page satisfies both the address and the content constraints
+    Impossible,
+    /// The constraints leave too many problems to solve; nothing was proven
+    BudgetExceeded,
+}
+
+/// Why a set of fragments can't be composed into one page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ComposeError {
+    /// No fragments were given
+    NoFragments,
+    /// Fragment `index` is empty or contains text outside the alphabet
+    InvalidText { index: usize },
+    /// Fragment `index` runs past the end of the page
+    OutOfBounds { index: usize },
+    /// Fragments `first` and `second` share at least one cluster position
+    Overlap { first: usize, second: usize },
+}
+
+impl std::fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComposeError::NoFragments => write!(f, "no fragments to compose"),
+            ComposeError::InvalidText { index } => {
+                write!(f, "fragment {} is empty or not Kannada text", index)
+            }
+            ComposeError::OutOfBounds { index } => {
+                write!(f, "fragment {} runs past the end of the page", index)
+            }
+            ComposeError::Overlap { first, second } => {
+                write!(f, "fragments {} and {} overlap", first, second)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComposeError {}
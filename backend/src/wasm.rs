@@ -1,9 +1,15 @@
 use wasm_bindgen::prelude::*;
-use crate::{LibraryOfBabel, Location, HierarchicalAddress};
+use num_bigint::BigUint;
+use crate::{
+    entropy_rng, rng_from_seed, seeded_rng, CalendarDate, CoherenceModel, Direction, Filler,
+    Layout, Level, LibraryOfBabel, LibraryRng, Location, Placement, SearchResult,
+    IST_OFFSET_SECONDS,
+};
 
 #[wasm_bindgen]
 pub struct WasmLibrary {
     library: LibraryOfBabel,
+    coherence: Option<CoherenceModel>,
 }
 
 #[wasm_bindgen]
@@ -15,6 +21,7 @@ impl WasmLibrary {
 
         Self {
             library: LibraryOfBabel::new(),
+            coherence: None,
         }
     }
 
@@ -35,31 +42,35 @@ impl WasmLibrary {
         }).to_string()
     }
 
+    /// Get a page laid out by `layout` (fixed, wrap, justified or verse) at
+    /// `width` clusters per line, with the cluster range of every line
+    #[wasm_bindgen(js_name = getPageLaidOut)]
+    pub fn get_page_laid_out(&self, address: &str, layout: &str, width: usize) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+        let layout = match Layout::from_name(layout, width) {
+            Some(layout) => layout,
+            None => return Self::error_json("Layout must be fixed, wrap, justified or verse"),
+        };
+
+        let page = self.library.layout_page(&self.library.generate_page(&location), layout);
+        serde_json::json!({
+            "raw_address": page.location.raw_hex,
+            "hierarchical": self.build_hierarchical(&page.location),
+            "content": page.content,
+            "formatted_content": page.formatted_content,
+            "lines": page.lines,
+        }).to_string()
+    }
+
     /// Find the address for given text (appears at start of page)
     #[wasm_bindgen(js_name = findText)]
     pub fn find_text(&self, text: &str) -> String {
         match self.library.search(text) {
-            Some(result) => {
-                let page = self.library.generate_page(&result.location);
-                let preview: String = page.content.chars().take(80).collect();
-                serde_json::json!({
-                    "query": result.query,
-                    "found": true,
-                    "location": {
-                        "raw_address": result.location.raw_hex,
-                        "hierarchical": self.build_hierarchical(&result.location),
-                    },
-                    "page_preview": preview,
-                }).to_string()
-            }
-            None => {
-                serde_json::json!({
-                    "query": text,
-                    "found": false,
-                    "location": null,
-                    "page_preview": null,
-                }).to_string()
-            }
+            Some(result) => self.search_result_json(&result.query, Some(&result)).to_string(),
+            None => self.search_result_json(text, None).to_string(),
         }
     }
 
@@ -67,44 +78,189 @@ impl WasmLibrary {
     #[wasm_bindgen(js_name = searchText)]
     pub fn search_text(&self, query: &str) -> String {
         match self.library.search_at_random_position(query) {
-            Some(result) => {
-                let page = self.library.generate_page(&result.location);
-                let preview: String = page.content.chars().take(80).collect();
+            Some(result) => self.search_result_json(&result.query, Some(&result)).to_string(),
+            None => self.search_result_json(query, None).to_string(),
+        }
+    }
+
+    /// Search for text at a chosen placement, given as JSON such as
+    /// `{"kind":"line_column","line":7,"column":10}` or `{"kind":"page_end"}`,
+    /// surrounded by `filler` (blank, uniform, kannada or repeat; empty for blank)
+    #[wasm_bindgen(js_name = searchAt)]
+    pub fn search_at(&self, query: &str, placement: &str, filler: &str, seed: Option<u64>) -> String {
+        let placement: Placement = match serde_json::from_str(placement) {
+            Ok(placement) => placement,
+            Err(_) => return Self::error_json("Invalid placement"),
+        };
+        let filler = match filler {
+            "" => Filler::Blank,
+            name => match Filler::from_name(name) {
+                Some(filler) => filler,
+                None => return Self::error_json("Invalid filler"),
+            },
+        };
+        let mut rng = rng_from_seed(seed);
+
+        match self.library.search_filled_with(query, placement, filler, &mut rng) {
+            Some(result) => self.search_result_json(&result.query, Some(&result)).to_string(),
+            None => self.search_result_json(query, None).to_string(),
+        }
+    }
+
+    /// Search for text matching a pattern at a placement (as for `searchAt`).
+    /// With `random`, a random match is chosen and surrounded by `filler`;
+    /// otherwise the first match is used with blank surroundings.
+    #[wasm_bindgen(js_name = searchPattern)]
+    pub fn search_pattern(
+        &self,
+        pattern: &str,
+        placement: &str,
+        random: bool,
+        filler: &str,
+        seed: Option<u64>,
+    ) -> String {
+        let placement: Placement = match serde_json::from_str(placement) {
+            Ok(placement) => placement,
+            Err(_) => return Self::error_json("Invalid placement"),
+        };
+        let filler = match filler {
+            "" => Filler::Blank,
+            name => match Filler::from_name(name) {
+                Some(filler) => filler,
+                None => return Self::error_json("Invalid filler"),
+            },
+        };
+
+        let result = if random {
+            let mut rng = rng_from_seed(seed);
+            self.library.search_pattern_random_with(pattern, placement, filler, &mut rng)
+        } else {
+            self.library.search_pattern(pattern, placement)
+        };
+
+        match result {
+            Ok(result) => {
+                let mut json = self.search_result_json(&result.query, Some(&result));
+                json["pattern"] = pattern.into();
+                json.to_string()
+            }
+            Err(error) => Self::error_json(&error.to_string()),
+        }
+    }
+
+    /// Search for the occurrence with the smallest address, trying `budget` positions
+    #[wasm_bindgen(js_name = searchShortest)]
+    pub fn search_shortest(&self, query: &str, budget: usize) -> String {
+        match self.library.search_shortest(query, budget) {
+            Some(result) => self.search_result_json(&result.query, Some(&result)).to_string(),
+            None => Self::error_json("Invalid query"),
+        }
+    }
+
+    /// Up to `limit` distinct pages containing `query`, resuming at a decimal
+    /// `token` ("" for the start), plus the token to continue from
+    pub fn occurrences(&self, query: &str, token: &str, limit: usize) -> String {
+        let token = match token {
+            "" => BigUint::default(),
+            token => match token.parse() {
+                Ok(token) => token,
+                Err(_) => return Self::error_json("Invalid token"),
+            },
+        };
+        let Some(mut occurrences) = self.library.occurrences_from(query, token) else {
+            return Self::error_json("Invalid query");
+        };
+
+        let found: Vec<serde_json::Value> = occurrences
+            .by_ref()
+            .take(limit)
+            .map(|occurrence| {
+                let location = &occurrence.result.location;
                 serde_json::json!({
-                    "query": result.query,
-                    "found": true,
+                    "token": occurrence.token.to_string(),
                     "location": {
-                        "raw_address": result.location.raw_hex,
-                        "hierarchical": self.build_hierarchical(&result.location),
+                        "raw_address": location.raw_hex,
+                        "hierarchical": self.build_hierarchical(location),
                     },
-                    "page_preview": preview,
-                }).to_string()
+                    "span": occurrence.result.span,
+                })
+            })
+            .collect();
+        let next_token = (found.len() == limit).then(|| occurrences.next_token().to_string());
+
+        serde_json::json!({
+            "query": query,
+            "occurrences": found,
+            "next_token": next_token,
+        }).to_string()
+    }
+
+    /// Browse random pages
+    #[wasm_bindgen(js_name = browseRandom)]
+    pub fn browse_random(&self, count: usize) -> String {
+        self.browse_random_from(count, &mut entropy_rng())
+    }
+
+    /// Browse random pages reproducibly from a seed
+    #[wasm_bindgen(js_name = browseRandomSeeded)]
+    pub fn browse_random_seeded(&self, count: usize, seed: u64) -> String {
+        self.browse_random_from(count, &mut seeded_rng(seed))
+    }
+
+    /// Page of the day for `date` (YYYY-MM-DD, empty for today in IST), as on /api/daily
+    #[wasm_bindgen(js_name = pageOfTheDay)]
+    pub fn page_of_the_day(&self, date: &str, salt: Option<String>) -> String {
+        let date = match date {
+            "" => {
+                let now = (js_sys::Date::now() / 1000.0).floor() as i64;
+                CalendarDate::from_unix_seconds(now, IST_OFFSET_SECONDS)
             }
-            None => {
+            date => match CalendarDate::parse(date) {
+                Some(date) => date,
+                None => return Self::error_json("Date must be YYYY-MM-DD"),
+            },
+        };
+
+        let page = self.library.page_of_the_day(&date, salt.as_deref());
+        serde_json::json!({
+            "date": date.to_string(),
+            "raw_address": page.location.raw_hex,
+            "hierarchical": self.build_hierarchical(&page.location),
+            "content": page.content,
+            "formatted_content": page.formatted_content,
+        }).to_string()
+    }
+
+    /// Random page within the book, shelf, wall or room of the given address
+    #[wasm_bindgen(js_name = randomPageWithin)]
+    pub fn random_page_within(&self, address: &str, level: &str) -> String {
+        let (scope, level) = match (self.library.parse_address(address), Level::from_name(level)) {
+            (Some(scope), Some(level)) => (scope, level),
+            _ => return Self::error_json("Invalid address or level"),
+        };
+
+        match self.library.random_location_in(&scope, level) {
+            Some(location) => {
+                let page = self.library.generate_page(&location);
                 serde_json::json!({
-                    "query": query,
-                    "found": false,
-                    "location": null,
-                    "page_preview": null,
+                    "success": true,
+                    "raw_address": page.location.raw_hex,
+                    "hierarchical": self.build_hierarchical(&page.location),
+                    "content": page.content,
+                    "formatted_content": page.formatted_content,
                 }).to_string()
             }
+            None => Self::error_json("Address is outside the library"),
         }
     }
 
-    /// Browse random pages
-    #[wasm_bindgen(js_name = browseRandom)]
-    pub fn browse_random(&self, count: usize) -> String {
-        let mut responses = Vec::with_capacity(count);
-        for _ in 0..count {
-            let page = self.library.random_page();
-            responses.push(serde_json::json!({
-                "raw_address": page.location.raw_hex,
-                "hierarchical": self.build_hierarchical(&page.location),
-                "content": page.content,
-                "formatted_content": page.formatted_content,
-            }));
+    /// Search for text at a random position, reproducibly from a seed
+    #[wasm_bindgen(js_name = searchTextSeeded)]
+    pub fn search_text_seeded(&self, query: &str, seed: u64) -> String {
+        match self.library.search_at_random_position_seeded(query, seed) {
+            Some(result) => self.search_result_json(&result.query, Some(&result)).to_string(),
+            None => self.search_result_json(query, None).to_string(),
         }
-        serde_json::to_string(&responses).unwrap_or_else(|_| "[]".to_string())
     }
 
     /// Get the next page after the given address
@@ -115,14 +271,23 @@ impl WasmLibrary {
             None => return Self::error_json("Invalid address format"),
         };
 
-        let page = self.library.next_page(&location);
-        serde_json::json!({
-            "success": true,
-            "raw_address": page.location.raw_hex,
-            "hierarchical": self.build_hierarchical(&page.location),
-            "content": page.content,
-            "formatted_content": page.formatted_content,
-        }).to_string()
+        match self.library.next_page(&location) {
+            Some(page) => {
+                serde_json::json!({
+                    "success": true,
+                    "raw_address": page.location.raw_hex,
+                    "hierarchical": self.build_hierarchical(&page.location),
+                    "content": page.content,
+                    "formatted_content": page.formatted_content,
+                }).to_string()
+            }
+            None => {
+                serde_json::json!({
+                    "success": false,
+                    "error": "Already at the last page of the library"
+                }).to_string()
+            }
+        }
     }
 
     /// Get the previous page before the given address
@@ -151,19 +316,270 @@ impl WasmLibrary {
             }
         }
     }
+
+    /// Where `text` occurs on the page at `address`, with a diff and a proof
+    pub fn verify(&self, address: &str, text: &str) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+
+        match self.library.verify_detailed(&location, text) {
+            Some(verification) => serde_json::to_string(&verification)
+                .unwrap_or_else(|_| Self::error_json("Serialization failed")),
+            None => Self::error_json("Text is not made of Kannada clusters"),
+        }
+    }
+
+    /// Encrypt `text` with `passphrase` onto a fresh random page
+    #[wasm_bindgen(js_name = hideMessage)]
+    pub fn hide_message(&self, text: &str, passphrase: &str, seed: Option<u64>) -> String {
+        let mut rng = rng_from_seed(seed);
+
+        match self.library.hide_message_with(text, passphrase, &mut rng) {
+            Some(location) => {
+                let page = self.library.generate_page(&location);
+                serde_json::json!({
+                    "raw_address": page.location.raw_hex,
+                    "hierarchical": self.build_hierarchical(&page.location),
+                    "content": page.content,
+                    "formatted_content": page.formatted_content,
+                }).to_string()
+            }
+            None => Self::error_json("Message must be Kannada text that fits on a page"),
+        }
+    }
+
+    /// Decrypt the message hidden at `address`
+    pub fn reveal(&self, address: &str, passphrase: &str) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+
+        match self.library.reveal(&location, passphrase) {
+            Some(text) => serde_json::json!({ "address": address, "text": text }).to_string(),
+            None => Self::error_json("Wrong passphrase or no message on this page"),
+        }
+    }
+
+    /// Build the coherence model from the text of a word list (one word per
+    /// line, optionally with a count); returns the number of words kept
+    #[wasm_bindgen(js_name = loadWordList)]
+    pub fn load_word_list(&mut self, list: &str) -> usize {
+        let model = CoherenceModel::from_word_list(list, self.library.alphabet());
+        let words = model.word_count();
+        self.coherence = Some(model);
+        words
+    }
+
+    /// Score `samples` random pages against the loaded word list and keep the best `keep`
+    #[wasm_bindgen(js_name = findCoherentPages)]
+    pub fn find_coherent_pages(&self, samples: usize, keep: usize, seed: Option<u64>) -> String {
+        let Some(model) = &self.coherence else {
+            return Self::error_json("Load a word list first");
+        };
+        let mut rng = rng_from_seed(seed);
+
+        let pages: Vec<serde_json::Value> = self.library
+            .find_coherent_pages_with(model, samples, keep, &mut rng)
+            .into_iter()
+            .map(|scored| serde_json::json!({
+                "location": {
+                    "raw_address": scored.location.raw_hex,
+                    "hierarchical": self.build_hierarchical(&scored.location),
+                },
+                "content": scored.content,
+                "score": scored.score,
+            }))
+            .collect();
+
+        serde_json::json!({ "samples": samples, "pages": pages }).to_string()
+    }
+
+    /// Exact odds of a random page holding `query`, at the start or anywhere
+    pub fn probability(&self, query: &str) -> String {
+        match self.library.text_probability(query) {
+            Some(probability) => serde_json::to_string(&probability)
+                .unwrap_or_else(|_| Self::error_json("Serialization failed")),
+            None => Self::error_json("Query must be non-empty Kannada text"),
+        }
+    }
+
+    /// Every step from `query` to its address, for the bijection visualizer
+    #[wasm_bindgen(js_name = explainSearch)]
+    pub fn explain_search(&self, query: &str) -> String {
+        match self.library.explain_search(query) {
+            Some(explanation) => serde_json::to_string(&explanation)
+                .unwrap_or_else(|_| Self::error_json("Serialization failed")),
+            None => Self::error_json("Query must be Kannada text that fits on a page"),
+        }
+    }
+
+    /// Every step from `address` to the text of its page
+    #[wasm_bindgen(js_name = explainPage)]
+    pub fn explain_page(&self, address: &str) -> String {
+        match self.library.parse_address(address) {
+            Some(location) => serde_json::to_string(&self.library.explain_page(&location))
+                .unwrap_or_else(|_| Self::error_json("Serialization failed")),
+            None => Self::error_json("Invalid address format"),
+        }
+    }
+
+    /// Get every page of the book containing the given address
+    #[wasm_bindgen(js_name = getBook)]
+    pub fn get_book(&self, address: &str) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+
+        let book = self.library.generate_book(&location);
+        let pages: Vec<serde_json::Value> = book.pages
+            .iter()
+            .map(|page| serde_json::json!({
+                "raw_address": page.location.raw_hex,
+                "hierarchical": self.build_hierarchical(&page.location),
+                "content": page.content,
+                "formatted_content": page.formatted_content,
+            }))
+            .collect();
+
+        serde_json::json!({
+            "success": true,
+            "location": {
+                "raw_address": book.location.raw_hex,
+                "hierarchical": self.build_hierarchical(&book.location),
+            },
+            "title": book.title,
+            "pages": pages,
+        }).to_string()
+    }
+
+    /// Get the spine titles of every book on the shelf containing the given address
+    #[wasm_bindgen(js_name = getShelf)]
+    pub fn get_shelf(&self, address: &str) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+
+        let books: Vec<serde_json::Value> = self.library
+            .shelf(&location)
+            .iter()
+            .map(|spine| serde_json::json!({
+                "pustaka": spine.pustaka,
+                "title": spine.title,
+                "location": {
+                    "raw_address": spine.location.raw_hex,
+                    "hierarchical": self.build_hierarchical(&spine.location),
+                },
+            }))
+            .collect();
+
+        serde_json::json!({
+            "success": true,
+            "gode": location.hierarchical.gode,
+            "patti": location.hierarchical.patti,
+            "books": books,
+        }).to_string()
+    }
+
+    /// Rooms adjacent to the room of the given address
+    #[wasm_bindgen]
+    pub fn neighbours(&self, address: &str) -> String {
+        let location = match self.library.parse_address(address) {
+            Some(loc) => loc,
+            None => return Self::error_json("Invalid address format"),
+        };
+
+        let towards = |direction| {
+            let neighbour = self.library.walk(&location, direction);
+            serde_json::json!({
+                "raw_address": neighbour.raw_hex,
+                "hierarchical": self.build_hierarchical(&neighbour),
+            })
+        };
+
+        serde_json::json!({
+            "success": true,
+            "up": towards(Direction::Up),
+            "down": towards(Direction::Down),
+            "left": towards(Direction::Left),
+            "right": towards(Direction::Right),
+        }).to_string()
+    }
+
+    /// Walk to the adjacent room in a direction (up, down, left or right)
+    #[wasm_bindgen]
+    pub fn walk(&self, address: &str, direction: &str) -> String {
+        let (location, direction) = match (
+            self.library.parse_address(address),
+            Direction::from_name(direction),
+        ) {
+            (Some(loc), Some(direction)) => (loc, direction),
+            _ => return Self::error_json("Invalid address or direction"),
+        };
+
+        let page = self.library.generate_page(&self.library.walk(&location, direction));
+        serde_json::json!({
+            "success": true,
+            "raw_address": page.location.raw_hex,
+            "hierarchical": self.build_hierarchical(&page.location),
+            "content": page.content,
+            "formatted_content": page.formatted_content,
+        }).to_string()
+    }
 }
 
 // Private helper methods (not exposed to JS)
 impl WasmLibrary {
-    /// Parse address from hex or hierarchical format
-    fn parse_address(&self, address: &str) -> Option<Location> {
-        self.library.parse_address(address)
+    fn browse_random_from(&self, count: usize, rng: &mut LibraryRng) -> String {
+        let mut responses = Vec::with_capacity(count);
+        for _ in 0..count {
+            let page = self.library.random_page_with(rng);
+            responses.push(serde_json::json!({
+                "raw_address": page.location.raw_hex,
+                "hierarchical": self.build_hierarchical(&page.location),
+                "content": page.content,
+                "formatted_content": page.formatted_content,
+            }));
+        }
+        serde_json::to_string(&responses).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Search result JSON with a page preview (matches the server's make_search_response)
+    fn search_result_json(&self, query: &str, result: Option<&SearchResult>) -> serde_json::Value {
+        match result {
+            Some(result) => {
+                let page = self.library.generate_page(&result.location);
+                let preview: String = page.content.chars().take(80).collect();
+
+                serde_json::json!({
+                    "query": query,
+                    "found": true,
+                    "location": {
+                        "raw_address": result.location.raw_hex,
+                        "hierarchical": self.build_hierarchical(&result.location),
+                    },
+                    "page_preview": preview,
+                    "span": result.span,
+                })
+            }
+            None => serde_json::json!({
+                "query": query,
+                "found": false,
+                "location": null,
+                "page_preview": null,
+                "span": null,
+            }),
+        }
     }
 
     /// Build hierarchical JSON with mandira_kannada (matches server behavior)
     fn build_hierarchical(&self, location: &Location) -> serde_json::Value {
-        let mandira_kannada = self.get_mandira_kannada(location);
-        
+        let mandira_kannada = self.library.mandira_as_kannada(&location.hierarchical.mandira);
+
         serde_json::json!({
             "mandira_hex": location.hierarchical.mandira_hex(),
             "mandira_kannada": mandira_kannada,
@@ -175,16 +591,6 @@ impl WasmLibrary {
         })
     }
 
-    /// Compute mandira as Kannada text (only for small mandira values)
-    /// Matches the server logic: only compute if bits < 10000
-    fn get_mandira_kannada(&self, location: &Location) -> Option<String> {
-        if location.hierarchical.mandira.bits() < 10000 {
-            Some(self.library.mandira_as_kannada(&location.hierarchical.mandira))
-        } else {
-            None
-        }
-    }
-
     /// Create a JSON error response
     fn error_json(message: &str) -> String {
         serde_json::json!({
@@ -0,0 +1,69 @@
+use akshara_mantapa::{ClusterKind, ClusterStats, GraphemeAlphabet};
+
+fn close(actual: f64, expected: f64) -> bool {
+    (actual - expected).abs() <= 1e-9 * expected.abs().max(1.0)
+}
+
+#[test]
+fn small_page_matches_hand_computed_figures() {
+    let alphabet = GraphemeAlphabet::new();
+    let n = alphabet.size() as f64;
+
+    // ಕ twice, then a space and ಅ once each
+    let page = alphabet.segment("ಕಕ ಅ").unwrap();
+    let stats = ClusterStats::compute(&[&page], &alphabet, 2);
+
+    assert_eq!((stats.pages, stats.clusters, stats.distinct_clusters), (1, 4, 3));
+    assert!(close(stats.expected_count, 4.0 / n));
+
+    // Σ o²/e - total = (4 + 1 + 1) / (4/N) - 4
+    assert!(close(stats.chi_square, 1.5 * n - 4.0), "{}", stats.chi_square);
+    // p = 1/2, 1/4, 1/4
+    assert!(close(stats.entropy_bits, 1.5));
+    assert!(close(stats.sample_limit_bits, 2.0));
+
+    let most_common: Vec<_> = stats.most_common.iter().map(|c| (c.cluster.as_str(), c.count)).collect();
+    assert_eq!(most_common[0], ("ಕ", 2));
+    assert_eq!(most_common.len(), 2);
+
+    let share = |kind| stats.kinds.iter().find(|k| k.kind == kind).unwrap();
+    assert_eq!(share(ClusterKind::Consonant).count, 2);
+    assert!(close(share(ClusterKind::Consonant).share, 0.5));
+    assert!(close(share(ClusterKind::Space).share, 0.25));
+    assert!(close(share(ClusterKind::Vowel).share, 0.25));
+    assert_eq!(share(ClusterKind::Conjunct).count, 0);
+    for kind in &stats.kinds {
+        let expected = alphabet.indices_of_kind(kind.kind).len() as f64 / n;
+        assert!(close(kind.expected_share, expected));
+    }
+    let expected_total: f64 = stats.kinds.iter().map(|k| k.expected_share).sum();
+    assert!(close(expected_total, 1.0));
+
+    let run = stats.longest_cluster_run.unwrap();
+    assert_eq!((run.value.as_str(), run.length, run.page, run.position), ("ಕ", 2, 0, 0));
+}
+
+#[test]
+fn uniform_counts_have_zero_chi_square() {
+    let alphabet = GraphemeAlphabet::new();
+    let n = alphabet.size();
+
+    // Every cluster exactly twice, across two pages
+    let page: Vec<usize> = (0..n).collect();
+    let stats = ClusterStats::compute(&[&page, &page], &alphabet, 5);
+
+    assert_eq!(stats.clusters, 2 * n);
+    assert!(close(stats.expected_count, 2.0));
+    assert!(stats.chi_square.abs() < 1e-6, "{}", stats.chi_square);
+    assert!(close(stats.entropy_bits, stats.max_entropy_bits));
+    assert!(stats.kinds.iter().all(|k| close(k.share, k.expected_share)));
+}
+
+#[test]
+fn empty_input_has_no_statistics() {
+    let alphabet = GraphemeAlphabet::new();
+    let stats = ClusterStats::compute(&[], &alphabet, 5);
+
+    assert_eq!((stats.clusters, stats.chi_square, stats.entropy_bits), (0, 0.0, 0.0));
+    assert!(stats.most_common.is_empty() && stats.longest_cluster_run.is_none());
+}
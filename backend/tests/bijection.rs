@@ -0,0 +1,82 @@
+use akshara_mantapa::BijectionEngine;
+use num_bigint::BigUint;
+
+/// Smallest x with lo ≤ (base + a·x) mod m ≤ hi, by trying every x below m
+fn brute_force(base: u64, a: u64, m: u64, lo: u64, hi: u64) -> Option<u64> {
+    (0..m).find(|x| (lo..=hi).contains(&((base + a * x) % m)))
+}
+
+fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+#[test]
+fn smallest_in_range_matches_brute_force() {
+    for m in 1..=30u64 {
+        for a in 0..m {
+            for lo in 0..m {
+                for hi in lo..m {
+                    let found =
+                        BijectionEngine::smallest_in_range(&big(a), &big(m), &big(lo), &big(hi));
+                    assert_eq!(
+                        found,
+                        brute_force(0, a, m, lo, hi).map(big),
+                        "a={} m={} [{}, {}]",
+                        a, m, lo, hi,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn smallest_from_matches_brute_force() {
+    for m in 1..=16u64 {
+        for base in 0..m {
+            for a in 0..m {
+                for lo in 0..m {
+                    for hi in lo..m {
+                        let found = BijectionEngine::smallest_from(
+                            &big(base), &big(a), &big(m), &big(lo), &big(hi),
+                        );
+                        assert_eq!(
+                            found,
+                            brute_force(base, a, m, lo, hi).map(big),
+                            "base={} a={} m={} [{}, {}]",
+                            base, a, m, lo, hi,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn solvers_match_brute_force_on_larger_moduli() {
+    // Powers of a small base, like the N^k moduli the library solves against
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % bound
+    };
+
+    for m in [7u64.pow(4), 12u64.pow(3), 57324] {
+        for _ in 0..200 {
+            let (a, base) = (next(m), next(m));
+            let lo = next(m);
+            let hi = lo + next((m - lo).min(20));
+            assert_eq!(
+                BijectionEngine::smallest_in_range(&big(a), &big(m), &big(lo), &big(hi)),
+                brute_force(0, a, m, lo, hi).map(big),
+            );
+            assert_eq!(
+                BijectionEngine::smallest_from(&big(base), &big(a), &big(m), &big(lo), &big(hi)),
+                brute_force(base, a, m, lo, hi).map(big),
+            );
+        }
+    }
+}
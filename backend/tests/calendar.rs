@@ -0,0 +1,70 @@
+use akshara_mantapa::{CalendarDate, IST_OFFSET_SECONDS};
+
+fn date(year: i32, month: u32, day: u32) -> CalendarDate {
+    CalendarDate::new(year, month, day).unwrap()
+}
+
+#[test]
+fn known_days_since_epoch() {
+    for (days, expected) in [
+        (0, date(1970, 1, 1)),
+        (-1, date(1969, 12, 31)),
+        (11_016, date(2000, 2, 29)),
+        (11_017, date(2000, 3, 1)),
+        (19_782, date(2024, 2, 29)),
+        (-25_509, date(1900, 2, 28)),
+        (-25_508, date(1900, 3, 1)),
+    ] {
+        assert_eq!(CalendarDate::from_days_since_epoch(days), expected, "day {}", days);
+    }
+}
+
+#[test]
+fn consecutive_days_are_consecutive_dates() {
+    // Walk day by day from 1600 to 2400 with a plain successor function
+    let next = |d: CalendarDate| {
+        CalendarDate::new(d.year, d.month, d.day + 1)
+            .or_else(|| CalendarDate::new(d.year, d.month + 1, 1))
+            .unwrap_or_else(|| date(d.year + 1, 1, 1))
+    };
+
+    let start = -135_140;
+    let mut expected = date(1600, 1, 1);
+    assert_eq!(CalendarDate::from_days_since_epoch(start), expected);
+    for days in start + 1..start + 292_194 {
+        expected = next(expected);
+        assert_eq!(CalendarDate::from_days_since_epoch(days), expected, "day {}", days);
+    }
+    assert_eq!(expected, date(2399, 12, 31));
+}
+
+#[test]
+fn the_day_changes_at_ist_midnight() {
+    // 2024-01-01 18:30:00 UTC is midnight in India
+    let midnight = 1_704_133_800;
+
+    assert_eq!(CalendarDate::from_unix_seconds(midnight - 1, IST_OFFSET_SECONDS), date(2024, 1, 1));
+    assert_eq!(CalendarDate::from_unix_seconds(midnight, IST_OFFSET_SECONDS), date(2024, 1, 2));
+    assert_eq!(CalendarDate::from_unix_seconds(midnight - 1, 0), date(2024, 1, 1));
+    assert_eq!(CalendarDate::from_unix_seconds(midnight + 6 * 3600, 0), date(2024, 1, 2));
+
+    assert_eq!(CalendarDate::seconds_until_midnight(midnight - 1, IST_OFFSET_SECONDS), 1);
+    assert_eq!(CalendarDate::seconds_until_midnight(midnight, IST_OFFSET_SECONDS), 86_400);
+    assert_eq!(CalendarDate::seconds_until_midnight(midnight + 1, IST_OFFSET_SECONDS), 86_399);
+    assert_eq!(CalendarDate::seconds_until_midnight(midnight, 0), 5 * 3600 + 30 * 60);
+
+    // Before the epoch too: 1970-01-01 began in India at -5:30 Unix time
+    let epoch_midnight = -IST_OFFSET_SECONDS;
+    let ist = |seconds| CalendarDate::from_unix_seconds(seconds, IST_OFFSET_SECONDS);
+    assert_eq!(ist(epoch_midnight), date(1970, 1, 1));
+    assert_eq!(ist(epoch_midnight - 1), date(1969, 12, 31));
+    assert_eq!(CalendarDate::seconds_until_midnight(epoch_midnight - 1, IST_OFFSET_SECONDS), 1);
+}
+
+#[test]
+fn dates_parse_and_print_as_iso() {
+    assert_eq!(CalendarDate::parse("2000-02-29"), Some(date(2000, 2, 29)));
+    assert_eq!(CalendarDate::parse("1900-02-29"), None);
+    assert_eq!(CalendarDate::parse("2024-13-01"), None);
+    assert_eq!(date(987, 6, 5).to_string(), "0987-06-05");
+}
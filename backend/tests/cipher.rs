@@ -0,0 +1,29 @@
+use akshara_mantapa::{seeded_rng, LibraryOfBabel};
+
+#[test]
+fn only_the_right_passphrase_reveals_a_message() {
+    let library = LibraryOfBabel::new();
+    let message = "ಕವಿರಾಜಮಾರ್ಗ ";
+
+    let location = library.hide_message_with(message, "ಪಂಪ", &mut seeded_rng(7)).unwrap();
+    assert_eq!(library.reveal(&location, "ಪಂಪ").as_deref(), Some(message));
+
+    for wrong in ["ಪಂಪಾ", "", "pampa"] {
+        assert_eq!(library.reveal(&location, wrong), None, "passphrase {:?}", wrong);
+    }
+}
+
+#[test]
+fn ordinary_pages_hold_no_message() {
+    let library = LibraryOfBabel::new();
+    let location = library.search("ಕನ್ನಡ").unwrap().location;
+    assert_eq!(library.reveal(&location, "ಪಂಪ"), None);
+}
+
+#[test]
+fn messages_must_fit() {
+    let library = LibraryOfBabel::new();
+    let too_long = "ಕ".repeat(library.message_capacity() + 1);
+    assert!(library.hide_message_with(&too_long, "ಪಂಪ", &mut seeded_rng(7)).is_none());
+    assert!(library.hide_message_with("", "ಪಂಪ", &mut seeded_rng(7)).is_none());
+}
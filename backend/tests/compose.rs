@@ -0,0 +1,60 @@
+use akshara_mantapa::{seeded_rng, ComposeError, Filler, LibraryGeometry, LibraryOfBabel};
+
+fn compose(library: &LibraryOfBabel, fragments: &[(usize, &str)]) -> Result<(), ComposeError> {
+    library.compose_page_with(fragments, Filler::Uniform, &mut seeded_rng(0)).map(drop)
+}
+
+#[test]
+fn fragments_land_at_their_offsets() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let alphabet = library.alphabet();
+    let fragments = [(0, "ಕನ್ನಡ"), (12, "ಮನೆ"), (37, "ಅಆಇ")];
+
+    for filler in [Filler::Blank, Filler::Uniform, Filler::Kannada, Filler::RepeatQuery] {
+        let location = library.compose_page_with(&fragments, filler, &mut seeded_rng(5)).unwrap();
+        let page = library.generate_page(&location).cluster_indices;
+        for (position, text) in fragments {
+            let indices = alphabet.segment(text).unwrap();
+            assert_eq!(&page[position..position + indices.len()], indices.as_slice(), "{:?}", filler);
+        }
+    }
+
+    // Blank filler leaves every other cluster a space
+    let location = library.compose_page_with(&fragments, Filler::Blank, &mut seeded_rng(5)).unwrap();
+    let page = library.generate_page(&location).cluster_indices;
+    assert_eq!(page.iter().filter(|&&index| index != 0).count(), 3 + 2 + 3);
+}
+
+#[test]
+fn fragments_may_touch_the_page_edges_and_each_other() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+
+    assert_eq!(compose(&library, &[(0, "ಕ"), (1, "ಖ"), (39, "ಗ")]), Ok(()));
+    assert_eq!(compose(&library, &[(37, "ಕನ್ನಡ")]), Ok(()));
+}
+
+#[test]
+fn bad_fragments_are_reported() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+
+    assert_eq!(compose(&library, &[]), Err(ComposeError::NoFragments));
+    assert_eq!(compose(&library, &[(0, "ಕ"), (3, "")]), Err(ComposeError::InvalidText { index: 1 }));
+    assert_eq!(compose(&library, &[(0, "abc")]), Err(ComposeError::InvalidText { index: 0 }));
+
+    // ಕನ್ನಡ is three clusters, so it fits at 37 but not at 38
+    assert_eq!(compose(&library, &[(38, "ಕನ್ನಡ")]), Err(ComposeError::OutOfBounds { index: 0 }));
+    assert_eq!(compose(&library, &[(0, "ಕ"), (40, "ಕ")]), Err(ComposeError::OutOfBounds { index: 1 }));
+    assert_eq!(
+        compose(&library, &[(usize::MAX, "ಕ")]),
+        Err(ComposeError::OutOfBounds { index: 0 }),
+    );
+
+    assert_eq!(
+        compose(&library, &[(0, "ಕನ್ನಡ"), (10, "ಮನೆ"), (2, "ಅ")]),
+        Err(ComposeError::Overlap { first: 0, second: 2 }),
+    );
+    assert_eq!(
+        compose(&library, &[(20, "ಅ"), (10, "ಮನೆ"), (9, "ಕನ್ನಡ")]),
+        Err(ComposeError::Overlap { first: 1, second: 2 }),
+    );
+}
@@ -0,0 +1,160 @@
+use akshara_mantapa::{
+    AddressConstraint, ConstrainedSearch, HierarchicalAddress, LibraryGeometry, LibraryOfBabel,
+    Location,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// Every page the constraint allows, with its clusters, found by enumerating the room
+fn scan(library: &LibraryOfBabel, constraint: &AddressConstraint) -> Vec<(Location, Vec<usize>)> {
+    let g = library.geometry();
+    let levels = |fixed: Option<u32>, count: u32| match fixed {
+        Some(value) => value..=value,
+        None => 1..=count,
+    };
+
+    let mut pages = Vec::new();
+    for gode in levels(constraint.gode, g.walls_per_room) {
+        for patti in levels(constraint.patti, g.shelves_per_wall) {
+            for pustaka in levels(constraint.pustaka, g.books_per_shelf) {
+                for puta in levels(constraint.puta, g.pages_per_book) {
+                    let h = HierarchicalAddress {
+                        mandira: constraint.mandira.clone().unwrap(),
+                        gode,
+                        patti,
+                        pustaka,
+                        puta,
+                    };
+                    let location = Location::from_hierarchical(h, g).unwrap();
+                    if location.to_raw() < *library.page_count() {
+                        let page = library.generate_page(&location);
+                        pages.push((location, page.cluster_indices));
+                    }
+                }
+            }
+        }
+    }
+    pages
+}
+
+fn holds(content: &[usize], query: &[usize], position: Option<usize>) -> bool {
+    match position {
+        Some(position) => content[position..].starts_with(query),
+        None => content.windows(query.len()).any(|window| window == query),
+    }
+}
+
+fn satisfies(location: &Location, constraint: &AddressConstraint) -> bool {
+    let h = &location.hierarchical;
+    constraint.mandira.as_ref().is_none_or(|mandira| *mandira == h.mandira)
+        && constraint.gode.is_none_or(|gode| gode == h.gode)
+        && constraint.patti.is_none_or(|patti| patti == h.patti)
+        && constraint.pustaka.is_none_or(|pustaka| pustaka == h.pustaka)
+        && constraint.puta.is_none_or(|puta| puta == h.puta)
+}
+
+#[test]
+fn constrained_search_agrees_with_a_full_scan() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let alphabet = library.alphabet();
+    let last_room = library.room_count() - BigUint::one();
+
+    let rooms = [BigUint::from(123_456_789u64), last_room];
+    let shapes = [
+        (Some(2), Some(3), None, None),
+        (None, None, Some(17), None),
+        (None, Some(3), None, Some(205)),
+        (Some(4), None, None, Some(1)),
+        (Some(1), Some(5), Some(32), Some(410)),
+    ];
+
+    let mut found = 0;
+    let mut impossible = 0;
+    for mandira in &rooms {
+        for &(gode, patti, pustaka, puta) in &shapes {
+            let constraint =
+                AddressConstraint { mandira: Some(mandira.clone()), gode, patti, pustaka, puta };
+            let pages = scan(&library, &constraint);
+
+            // Texts taken from allowed pages must be found; the others are almost never there
+            let mut queries: Vec<(Vec<usize>, Option<usize>)> = vec![
+                (vec![7], None),
+                (vec![7], Some(0)),
+                (vec![31_000], Some(39)),
+            ];
+            if let Some((_, content)) = pages.get(pages.len() / 2) {
+                queries.push((content[5..6].to_vec(), Some(5)));
+                queries.push((content[30..32].to_vec(), None));
+            }
+
+            for (query, position) in queries {
+                let text = alphabet.indices_to_string(&query);
+                let outcome = library.search_constrained(&constraint, &text, position).unwrap();
+                let expected = pages.iter().any(|(_, content)| holds(content, &query, position));
+
+                match outcome {
+                    ConstrainedSearch::Found(result) => {
+                        assert!(expected, "found {:?} where a scan finds none", constraint);
+                        assert!(satisfies(&result.location, &constraint));
+                        let content = library.generate_page(&result.location).cluster_indices;
+                        assert!(content[result.span.position..].starts_with(&query));
+                        assert!(position.is_none_or(|position| position == result.span.position));
+                        found += 1;
+                    }
+                    ConstrainedSearch::Impossible => {
+                        assert!(!expected, "impossible for {:?} but a scan finds it", constraint);
+                        impossible += 1;
+                    }
+                    ConstrainedSearch::BudgetExceeded => panic!("budget exceeded for {:?}", constraint),
+                }
+            }
+        }
+    }
+    assert!(found > 0 && impossible > 0);
+}
+
+#[test]
+fn free_room_results_satisfy_the_constraint() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+
+    let constraint = AddressConstraint { patti: Some(2), puta: Some(409), ..Default::default() };
+    for (text, position) in [("ಕನ್ನಡ", Some(0)), ("ಕವಿರಾಜಮಾರ್ಗ", None), ("ಅ", Some(39))] {
+        let Some(ConstrainedSearch::Found(result)) =
+            library.search_constrained(&constraint, text, position)
+        else {
+            panic!("{} not found", text);
+        };
+        assert!(satisfies(&result.location, &constraint));
+        let page = library.generate_page(&result.location);
+        let query = library.alphabet().segment(text).unwrap();
+        assert!(page.cluster_indices[result.span.position..].starts_with(&query));
+    }
+}
+
+#[test]
+fn loose_constraints_stop_at_the_work_budget() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let g = library.geometry();
+
+    // A whole page lives at exactly one address: gode 1, puta 1 of room 5
+    let h = HierarchicalAddress {
+        mandira: BigUint::from(5u32),
+        gode: 1,
+        patti: 1,
+        pustaka: 1,
+        puta: 1,
+    };
+    let page = library.generate_page(&Location::from_hierarchical(h, g).unwrap());
+    let search = |constraint: AddressConstraint| {
+        library.search_constrained(&constraint, &page.content, Some(0)).unwrap()
+    };
+
+    let gode = |gode| AddressConstraint { gode: Some(gode), ..Default::default() };
+    let puta = |puta| AddressConstraint { puta: Some(puta), ..Default::default() };
+    assert!(matches!(search(gode(1)), ConstrainedSearch::Found(_)));
+    assert!(matches!(search(puta(1)), ConstrainedSearch::Found(_)));
+
+    // 640 in-room offsets fit the budget and are proven empty; a whole wall does not
+    assert!(matches!(search(puta(2)), ConstrainedSearch::Impossible));
+    assert!(matches!(search(gode(2)), ConstrainedSearch::BudgetExceeded));
+}
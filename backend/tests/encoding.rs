@@ -0,0 +1,92 @@
+use akshara_mantapa::{LibraryGeometry, LibraryOfBabel};
+
+fn libraries() -> [LibraryOfBabel; 2] {
+    [LibraryOfBabel::new(), LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet())]
+}
+
+/// Non-zero bytes ending in `zeros` zero bytes, `length` in all
+fn bytes(length: usize, zeros: usize) -> Vec<u8> {
+    (0..length)
+        .map(|i| if i + zeros >= length { 0 } else { (i % 255) as u8 + 1 })
+        .collect()
+}
+
+#[test]
+fn bytes_round_trip_up_to_capacity() {
+    for library in libraries() {
+        let capacity = library.byte_capacity();
+        assert!(capacity > 0);
+
+        for data in [
+            vec![],
+            vec![0],
+            vec![0x2a],
+            bytes(3, 2),
+            bytes(capacity - 1, 0),
+            bytes(capacity, 0),
+            bytes(capacity, 5),
+            vec![0; capacity],
+        ] {
+            let location = library.encode_bytes(&data).unwrap();
+            assert_eq!(library.decode_bytes(&location), Some(data.clone()), "{} bytes", data.len());
+        }
+
+        assert!(library.encode_bytes(&bytes(capacity + 1, 0)).is_none());
+    }
+}
+
+#[test]
+fn trailing_zeros_give_different_pages() {
+    for library in libraries() {
+        let pages: Vec<_> = [vec![], vec![0], vec![0, 0], vec![7], vec![7, 0]]
+            .iter()
+            .map(|data| library.encode_bytes(data).unwrap().raw_hex)
+            .collect();
+        for (i, page) in pages.iter().enumerate() {
+            assert!(!pages[..i].contains(page));
+        }
+    }
+}
+
+#[test]
+fn files_round_trip_across_pages() {
+    for library in libraries() {
+        let capacity = library.byte_capacity();
+
+        for (length, zeros, pages) in [
+            (0, 0, 1),
+            (1, 0, 1),
+            (capacity, 3, 1),
+            (capacity + 1, 0, 2),
+            (capacity + 1, 1, 2),
+            (3 * capacity, capacity + 2, 3),
+        ] {
+            let data = bytes(length, zeros);
+            let locations = library.encode_file(&data).unwrap();
+            assert_eq!(locations.len(), pages, "{} bytes", length);
+            assert_eq!(library.decode_file(&locations), Some(data));
+        }
+    }
+}
+
+#[test]
+#[ignore = "building a 34,000-cluster library takes minutes without optimisation"]
+fn capacity_stops_at_the_length_header() {
+    // Pages of 34,000 clusters hold over 67,000 bytes, more than a u16 length can count
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry {
+        clusters_per_page: 34_000,
+        ..LibraryGeometry::pamphlet()
+    });
+    let capacity = library.byte_capacity();
+    assert_eq!(capacity, u16::MAX as usize);
+
+    let data = bytes(capacity, 0);
+    let location = library.encode_bytes(&data).unwrap();
+    assert_eq!(library.decode_bytes(&location), Some(data));
+    assert!(library.encode_bytes(&bytes(capacity + 1, 0)).is_none());
+
+    let data = bytes(capacity + 10, 0);
+    let locations = library.encode_file(&data).unwrap();
+    assert_eq!(locations.len(), 2);
+    assert_eq!(library.decode_file(&locations), Some(data));
+}
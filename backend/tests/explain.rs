@@ -0,0 +1,96 @@
+use akshara_mantapa::{
+    BijectionEngine, HierarchySplit, LibraryGeometry, LibraryOfBabel, Location, ModularStep,
+};
+use num_bigint::BigUint;
+
+fn big(decimal: &str) -> BigUint {
+    decimal.parse().unwrap()
+}
+
+/// The step's numbers are consistent, and reduce `operand × factor` by `modulus`
+fn check_step(step: &ModularStep, operand: &BigUint, factor: &BigUint, modulus: &BigUint) {
+    assert_eq!(big(&step.operand), *operand);
+    assert_eq!(big(&step.factor), *factor);
+    assert_eq!(big(&step.modulus), *modulus);
+    assert_eq!(big(&step.product), operand * factor);
+    assert_eq!(big(&step.quotient) * modulus + big(&step.remainder), big(&step.product));
+    assert!(big(&step.remainder) < *modulus);
+}
+
+/// Each division is exact and the levels are those of `location`
+fn check_split(split: &HierarchySplit, location: &Location) {
+    let h = &location.hierarchical;
+    assert_eq!(big(&split.raw_address), location.to_raw());
+    assert_eq!(split.raw_hex, location.raw_hex);
+    assert_eq!(split.hierarchical, h.to_display_string());
+    assert_eq!(big(&split.mandira), h.mandira);
+    assert_eq!(split.mandira_hex, h.mandira_hex());
+
+    assert_eq!(split.steps.len(), 4);
+    let mut dividend = location.to_raw();
+    for (step, value) in split.steps.iter().zip([h.puta, h.pustaka, h.patti, h.gode]) {
+        assert_eq!(big(&step.dividend), dividend);
+        let quotient = big(&step.quotient);
+        assert_eq!(&quotient * step.divisor + step.remainder, dividend, "{}", step.level);
+        assert_eq!(step.value, value, "{}", step.level);
+        dividend = quotient;
+    }
+    assert_eq!(dividend, h.mandira);
+}
+
+fn libraries() -> [LibraryOfBabel; 2] {
+    [LibraryOfBabel::new(), LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet())]
+}
+
+#[test]
+fn search_explanation_follows_the_bijection() {
+    for library in libraries() {
+        let alphabet = library.alphabet();
+        let engine = BijectionEngine::new(alphabet.size(), library.geometry());
+        let query = "ಕನ್ನಡ";
+        let explanation = library.explain_search(query).unwrap();
+
+        let indices = alphabet.segment(query).unwrap();
+        let clusters: Vec<_> = explanation.clusters.iter().map(|c| (c.position, c.index)).collect();
+        assert_eq!(clusters, indices.iter().copied().enumerate().collect::<Vec<_>>());
+        assert_eq!(explanation.digits.len(), library.geometry().clusters_per_page);
+        assert!(explanation.digits.starts_with(&indices));
+
+        let content_num = engine.indices_to_biguint(&explanation.digits);
+        assert_eq!(big(&explanation.content_num), content_num);
+        check_step(&explanation.multiplication, &content_num, engine.multiplier(), engine.modulus());
+
+        let address = engine.content_to_address(&content_num);
+        assert_eq!(big(&explanation.multiplication.remainder), address);
+
+        let found = library.search(query).unwrap();
+        assert_eq!(found.location.to_raw(), address);
+        check_split(&explanation.address, &found.location);
+    }
+}
+
+#[test]
+fn page_explanation_follows_the_bijection() {
+    for library in libraries() {
+        let engine = BijectionEngine::new(library.alphabet().size(), library.geometry());
+        let location = library.parse_address("2a.3.4.5.6").unwrap();
+        let explanation = library.explain_page(&location);
+
+        let raw = location.to_raw();
+        check_split(&explanation.address, &location);
+        check_step(&explanation.multiplication, &raw, engine.inverse(), engine.modulus());
+
+        let content_num = engine.address_to_content(&raw);
+        assert_eq!(big(&explanation.content_num), content_num);
+        assert_eq!(big(&explanation.multiplication.remainder), content_num);
+
+        let page = library.generate_page(&location);
+        let page_length = library.geometry().clusters_per_page;
+        assert_eq!(explanation.digits, engine.biguint_to_indices(content_num, page_length));
+        assert_eq!(explanation.digits, page.cluster_indices);
+        assert_eq!(explanation.content, page.content);
+
+        let text: String = explanation.clusters.iter().map(|c| c.text.as_str()).collect();
+        assert_eq!(text, page.content);
+    }
+}
@@ -0,0 +1,85 @@
+use akshara_mantapa::{seeded_rng, GraphemeAlphabet, Layout, LibraryOfBabel, LineSpan};
+
+fn layouts(width: usize) -> [Layout; 4] {
+    [
+        Layout::Fixed,
+        Layout::WordWrap { width },
+        Layout::Justified { width },
+        Layout::Verse { width },
+    ]
+}
+
+/// Spans run through the page without gaps, and index the lines of the text
+fn assert_covers(indices: &[usize], text: &str, lines: &[LineSpan], layout: Layout) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut next_cluster = 0;
+    let mut next_char = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        assert_eq!(line.start, next_cluster, "{:?} line {}", layout, i);
+        assert!(line.start < line.end, "{:?} line {} is empty", layout, i);
+        assert_eq!(line.char_start, next_char, "{:?} line {}", layout, i);
+        assert!(line.char_start <= line.char_end);
+        assert!(!chars[line.char_start..line.char_end].contains(&'\n'));
+
+        next_cluster = line.end;
+        next_char = line.char_end + 1;
+    }
+    assert_eq!(next_cluster, indices.len(), "{:?} doesn't reach the end", layout);
+    assert_eq!(next_char.saturating_sub(1), chars.len(), "{:?}", layout);
+    assert_eq!(text.split('\n').count(), lines.len().max(1), "{:?}", layout);
+}
+
+#[test]
+fn lines_are_contiguous_and_cover_the_page() {
+    let alphabet = GraphemeAlphabet::new();
+    let texts = [
+        "ಕವಿರಾಜಮಾರ್ಗ ಕನ್ನಡ ನಾಡು। ಕಾವೇರಿಯಿಂದಮಾ ಗೋದಾವರಿವರಮಿರ್ದ ನಾಡದಾ। ",
+        "   ಮುಂದೆ   ಹಿಂದೆ  ",
+        "ಅಆಇಈಉಊಋಎಏಐಒಓಔಕಖಗಘಙಚಛಜಝಞಟಠಡಢಣತಥದಧನ",
+        "।।। । ।",
+        " ",
+        "",
+    ];
+
+    for text in texts {
+        let indices = alphabet.segment(text).unwrap();
+        for width in [1, 2, 7, 25, 400] {
+            for layout in layouts(width) {
+                let (formatted, lines) = layout.format(&indices, &alphabet, 25);
+                assert_covers(&indices, &formatted, &lines, layout);
+            }
+        }
+    }
+}
+
+#[test]
+fn library_pages_are_covered_in_every_layout() {
+    let library = LibraryOfBabel::new();
+    let mut rng = seeded_rng(46);
+
+    for _ in 0..20 {
+        let page = library.random_page_with(&mut rng);
+        for layout in layouts(30) {
+            let page = library.layout_page(&page, layout);
+            assert_covers(&page.cluster_indices, &page.formatted_content, &page.lines, layout);
+
+            for line in &page.lines {
+                let text: String = page
+                    .formatted_content
+                    .chars()
+                    .skip(line.char_start)
+                    .take(line.char_end - line.char_start)
+                    .collect();
+                let clusters = library
+                    .alphabet()
+                    .indices_to_string(&page.cluster_indices[line.start..line.end]);
+                match layout {
+                    Layout::Fixed => assert_eq!(text, clusters),
+                    Layout::Justified { .. } => {}
+                    _ => assert_eq!(text, clusters.trim_end_matches(' ')),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use akshara_mantapa::{AddressDistance, LibraryGeometry, LibraryOfBabel, Level, Location};
+use num_bigint::{BigInt, BigUint};
+use num_traits::One;
+
+#[test]
+fn navigation_stops_at_the_last_page() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let geometry = library.geometry();
+    let page_count = library.page_count();
+    let last_raw = page_count - BigUint::one();
+    let last = Location::from_raw_address(&last_raw, geometry);
+
+    assert!(last.next(page_count, geometry).is_none());
+    assert!(library.next_page(&last).is_none());
+    assert!(last.offset(&BigInt::one(), page_count, geometry).is_none());
+
+    let before = last.retreat(&BigUint::from(3u32), geometry).unwrap();
+    assert_eq!(before.advance(&BigUint::from(3u32), page_count, geometry), Some(last.clone()));
+    assert!(before.advance(&BigUint::from(4u32), page_count, geometry).is_none());
+    assert_eq!(before.offset(&BigInt::from(3), page_count, geometry), Some(last.clone()));
+}
+
+#[test]
+fn last_book_ends_at_the_last_page() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let last = Location::from_raw_address(&(library.page_count() - BigUint::one()), library.geometry());
+
+    let book = library.generate_book(&last);
+    let last_puta = book.pages.last().unwrap().location.hierarchical.puta;
+    assert_eq!(book.pages.len(), last_puta as usize);
+    assert_eq!(book.pages.last().unwrap().location, last);
+    for page in &book.pages {
+        assert_eq!(page.content, library.generate_page(&page.location).content);
+    }
+}
+
+fn levels() -> [Level; 5] {
+    [Level::Puta, Level::Pustaka, Level::Patti, Level::Gode, Level::Mandira]
+}
+
+/// The page count a distance's levels add up to, signed
+fn recombine(distance: &AddressDistance, geometry: &LibraryGeometry) -> BigInt {
+    let mut pages = distance.mandira.clone();
+    for (value, count) in [
+        (distance.gode, geometry.walls_per_room),
+        (distance.patti, geometry.shelves_per_wall),
+        (distance.pustaka, geometry.books_per_shelf),
+        (distance.puta, geometry.pages_per_book),
+    ] {
+        pages = pages * BigInt::from(count) + BigInt::from(value);
+    }
+    pages
+}
+
+#[test]
+fn jumps_and_distances_round_trip() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let geometry = library.geometry();
+    let page_count = library.page_count();
+    let start = Location::from_raw_address(&(page_count >> 1u32), geometry);
+
+    for level in levels() {
+        let span = BigInt::from(level.pages(geometry));
+        for count in [1, -1, 3, -7, 0] {
+            let count = BigInt::from(count);
+            let there = start.jump(level, &count, page_count, geometry).unwrap();
+            assert_eq!(there.jump(level, &-&count, page_count, geometry), Some(start.clone()));
+
+            let distance = start.distance_to(&there, geometry);
+            assert_eq!(distance.pages, &count * &span);
+            assert_eq!(recombine(&distance, geometry), distance.pages);
+
+            // The way back is the same distance with every level negated
+            let back = there.distance_to(&start, geometry);
+            assert_eq!(back.pages, -&distance.pages);
+            assert_eq!(back.mandira, -&distance.mandira);
+            assert_eq!(
+                (back.gode, back.patti, back.pustaka, back.puta),
+                (-distance.gode, -distance.patti, -distance.pustaka, -distance.puta),
+            );
+            assert_eq!(distance.is_zero(), count == BigInt::default());
+        }
+    }
+}
+
+#[test]
+fn distances_split_into_levels() {
+    let geometry = LibraryGeometry::pamphlet();
+    let book = geometry.pages_per_book as i64;
+
+    let distance = AddressDistance::from_pages(BigInt::from(-(2 * book + 5)), &geometry);
+    assert_eq!(
+        (distance.mandira.clone(), distance.gode, distance.patti, distance.pustaka, distance.puta),
+        (BigInt::default(), 0, 0, -2, -5),
+    );
+
+    let room = BigInt::from(geometry.pages_per_room());
+    let distance = AddressDistance::from_pages(&room * 3 + 1, &geometry);
+    assert_eq!(distance.mandira, BigInt::from(3));
+    assert_eq!((distance.gode, distance.patti, distance.pustaka, distance.puta), (0, 0, 0, 1));
+    assert_eq!(recombine(&distance, &geometry), &room * 3 + 1);
+}
+
+#[test]
+fn jumps_stop_at_the_ends_of_the_library() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let geometry = library.geometry();
+    let page_count = library.page_count();
+    let last = Location::from_raw_address(&(page_count - BigUint::one()), geometry);
+    let first = Location::from_raw_address(&BigUint::default(), geometry);
+    let one = BigInt::one();
+
+    for level in levels() {
+        assert!(last.jump(level, &one, page_count, geometry).is_none(), "{:?}", level);
+        assert!(first.jump(level, &-&one, page_count, geometry).is_none(), "{:?}", level);
+    }
+
+    // The last book is short: a page of the book before it past its last page
+    // has nothing to jump to, while the pages up to it do
+    let last_puta = last.hierarchical.puta;
+    assert!(last_puta < geometry.pages_per_book);
+    let previous_book = last.jump(Level::Pustaka, &-&one, page_count, geometry).unwrap();
+    assert_eq!(previous_book.jump(Level::Pustaka, &one, page_count, geometry), Some(last.clone()));
+
+    let past_the_end = previous_book.advance(&BigUint::one(), page_count, geometry).unwrap();
+    assert_eq!(past_the_end.hierarchical.puta, last_puta + 1);
+    assert!(past_the_end.jump(Level::Pustaka, &one, page_count, geometry).is_none());
+}
@@ -0,0 +1,91 @@
+use akshara_mantapa::{GraphemeAlphabet, LibraryOfBabel, Mandira, MANDIRA_SEPARATOR};
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// Small deterministic xorshift generator so the sweep is reproducible
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: &BigUint) -> BigUint {
+        let words = (bound.bits() / 64 + 1) as usize;
+        let digits: Vec<u32> = (0..words * 2).map(|_| self.next() as u32).collect();
+        BigUint::new(digits) % bound
+    }
+}
+
+fn assert_round_trip(library: &LibraryOfBabel, alphabet: &GraphemeAlphabet, mandira: &BigUint) {
+    let name = Mandira::to_kannada(mandira, alphabet);
+    assert_eq!(Mandira::from_kannada(&name, alphabet).as_ref(), Some(mandira), "name {:?}", name);
+
+    let address = format!("{}.4.5.32.410", name);
+    let location = library.parse_address(&address).expect("hierarchical Kannada address");
+    assert_eq!(&location.hierarchical.mandira, mandira);
+}
+
+#[test]
+fn kannada_names_round_trip_for_all_rooms() {
+    let library = LibraryOfBabel::new();
+    let alphabet = GraphemeAlphabet::new();
+    let base = BigUint::from(alphabet.size());
+    let rooms = library.room_count();
+
+    // Every room whose name has one or two clusters
+    for n in 0..(alphabet.size() as u32 * 3) {
+        assert_round_trip(&library, &alphabet, &BigUint::from(n));
+    }
+
+    // Boundaries where the name grows by one cluster, up to the last room
+    let mut boundary = BigUint::one();
+    let mut first_of_length = BigUint::from(0u32);
+    while first_of_length < rooms {
+        for m in [first_of_length.clone(), &first_of_length + 1u32] {
+            if m < rooms {
+                assert_round_trip(&library, &alphabet, &m);
+            }
+        }
+        if first_of_length > BigUint::from(0u32) {
+            assert_round_trip(&library, &alphabet, &(&first_of_length - 1u32));
+        }
+        boundary *= &base;
+        first_of_length += &boundary;
+    }
+    assert_round_trip(&library, &alphabet, &(&rooms - 1u32));
+
+    // Uniformly sampled rooms
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..200 {
+        assert_round_trip(&library, &alphabet, &rng.below(&rooms));
+    }
+}
+
+#[test]
+fn names_with_leading_spaces_are_distinct_rooms() {
+    let alphabet = GraphemeAlphabet::new();
+    let plain = Mandira::from_kannada("ಕನ್ನಡ", &alphabet).unwrap();
+    let spaced = Mandira::from_kannada("  ಕನ್ನಡ", &alphabet).unwrap();
+
+    assert_ne!(plain, spaced);
+    assert_eq!(Mandira::to_kannada(&spaced, &alphabet), "  ಕನ್ನಡ");
+}
+
+#[test]
+fn halant_final_clusters_keep_their_boundary() {
+    let alphabet = GraphemeAlphabet::new();
+    let ka_halant = alphabet.index_of("ಕ್").unwrap();
+    let ssa = alphabet.index_of("ಷ").unwrap();
+
+    let split = Mandira::from_indices(&[ka_halant, ssa], alphabet.size()).unwrap();
+    let name = Mandira::to_kannada(&split, &alphabet);
+    assert_eq!(name, format!("ಕ್{}ಷ", MANDIRA_SEPARATOR));
+    assert_eq!(Mandira::from_kannada(&name, &alphabet), Some(split.clone()));
+
+    let conjunct = Mandira::from_kannada("ಕ್ಷ", &alphabet).unwrap();
+    assert_ne!(conjunct, split);
+}
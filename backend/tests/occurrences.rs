@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use akshara_mantapa::{LibraryGeometry, LibraryOfBabel, Occurrence};
+use num_bigint::BigUint;
+
+fn first(library: &LibraryOfBabel, query: &str, token: BigUint, count: usize) -> Vec<Occurrence> {
+    library.occurrences_from(query, token).unwrap().take(count).collect()
+}
+
+#[test]
+fn token_zero_is_the_search_result() {
+    let library = LibraryOfBabel::new();
+
+    for query in ["ಕನ್ನಡ", "ಕವಿರಾಜಮಾರ್ಗ", " "] {
+        let occurrence = library.occurrences(query).unwrap().next().unwrap();
+        let found = library.search(query).unwrap();
+        assert_eq!(occurrence.token, BigUint::default());
+        assert_eq!(occurrence.result.location, found.location, "{:?}", query);
+        assert_eq!(occurrence.result.span, found.span);
+    }
+}
+
+#[test]
+fn resuming_continues_the_same_sequence() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+
+    // Blank filler is spaces, so a query of spaces skips most tokens early on
+    for query in ["ಕನ್ನಡ", "  "] {
+        let all = first(&library, query, BigUint::default(), 120);
+        assert_eq!(all.len(), 120);
+
+        let distinct: HashSet<_> = all.iter().map(|o| o.result.location.raw_hex.clone()).collect();
+        assert_eq!(distinct.len(), all.len(), "pages repeat for {:?}", query);
+
+        for k in [1, 2, 37, 38, 39, 40, 41, 77, 119] {
+            let resumed = first(&library, query, all[k].token.clone(), all.len() - k);
+            let expected: Vec<_> = all[k..].iter().map(|o| &o.result.location).collect();
+            let actual: Vec<_> = resumed.iter().map(|o| &o.result.location).collect();
+            assert_eq!(actual, expected, "resuming {:?} at {}", query, k);
+
+            // The token after a page leads to the next page, even across skipped tokens
+            let mut occurrences = library.occurrences(query).unwrap();
+            occurrences.by_ref().take(k).for_each(drop);
+            let next = first(&library, query, occurrences.next_token().clone(), 1);
+            assert_eq!(next[0].token, all[k].token);
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use akshara_mantapa::{
+    GraphemeAlphabet, LibraryOfBabel, Pattern, PatternError, Placement, MAX_PATTERN_CHARS,
+    MAX_PATTERN_DEPTH,
+};
+
+fn nested(depth: usize) -> String {
+    format!("{}ಕ{}", "(".repeat(depth), ")".repeat(depth))
+}
+
+#[test]
+fn nesting_is_limited() {
+    let alphabet = GraphemeAlphabet::new();
+
+    let deepest = Pattern::parse(&nested(MAX_PATTERN_DEPTH), &alphabet).unwrap();
+    assert_eq!(deepest.first_match(10).map(|text| text.len()), Some(1));
+
+    assert_eq!(
+        Pattern::parse(&nested(MAX_PATTERN_DEPTH + 1), &alphabet).unwrap_err(),
+        PatternError::TooDeep { position: MAX_PATTERN_DEPTH },
+    );
+}
+
+#[test]
+fn deeply_nested_query_is_an_error_not_a_crash() {
+    let library = LibraryOfBabel::new();
+    let placement = Placement::Position { position: 0 };
+
+    // Enough nesting to overflow the stack if the parser recursed into it
+    let error = library.search_pattern(&nested(6000), placement).unwrap_err();
+    assert_eq!(error, PatternError::TooLong);
+
+    let within_length = nested((MAX_PATTERN_CHARS - 1) / 2);
+    let error = library.search_pattern(&within_length, placement).unwrap_err();
+    assert!(matches!(error, PatternError::TooDeep { .. }), "{:?}", error);
+}
+
+#[test]
+fn chained_repetitions_multiply() {
+    let alphabet = GraphemeAlphabet::new();
+
+    let pattern = Pattern::parse(&"ಕ{1}".repeat(1000), &alphabet).unwrap();
+    assert_eq!(pattern.first_match(1000).map(|text| text.len()), Some(1000));
+
+    let pattern = Pattern::parse(&format!("ಕ{}", "{2}".repeat(8)), &alphabet).unwrap();
+    assert_eq!(pattern.first_match(400).map(|text| text.len()), Some(256));
+
+    let overflow = format!("ಕ{}", "{4294967296}".repeat(4));
+    assert!(matches!(
+        Pattern::parse(&overflow, &alphabet),
+        Err(PatternError::BadRepetition { .. })
+    ));
+}
@@ -0,0 +1,56 @@
+use akshara_mantapa::TextProbability;
+use num_bigint::BigUint;
+
+/// Every string of `length` letters from an alphabet of `size`
+fn all_strings(size: usize, length: usize) -> Vec<Vec<usize>> {
+    (0..size.pow(length as u32))
+        .map(|mut n| {
+            let mut digits = vec![0; length];
+            for digit in digits.iter_mut().rev() {
+                *digit = n % size;
+                n /= size;
+            }
+            digits
+        })
+        .collect()
+}
+
+#[test]
+fn counts_match_brute_force_on_tiny_alphabets() {
+    for size in 1..=3 {
+        for page_length in 0..=7 {
+            let pages = all_strings(size, page_length);
+            for query_length in 1..=4 {
+                for query in all_strings(size, query_length) {
+                    let probability = TextProbability::new("", &query, size, page_length);
+
+                    let anywhere = pages
+                        .iter()
+                        .filter(|page| page.windows(query_length).any(|window| window == query))
+                        .count();
+                    let at_start = pages.iter().filter(|page| page.starts_with(&query)).count();
+
+                    assert_eq!(
+                        probability.pages_with_text_anywhere,
+                        BigUint::from(anywhere),
+                        "{:?} on {} letters, {} per page",
+                        query, size, page_length,
+                    );
+                    assert_eq!(probability.pages_with_text_at_start, BigUint::from(at_start));
+                    assert_eq!(probability.total_pages, BigUint::from(pages.len()));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn probabilities_follow_from_the_counts() {
+    let probability = TextProbability::new("", &[1, 1], 2, 3);
+
+    // 011, 110 and 111 hold "11"; 110 and 111 start with it
+    assert_eq!(probability.pages_with_text_anywhere, BigUint::from(3u32));
+    assert_eq!(probability.pages_with_text_at_start, BigUint::from(2u32));
+    assert_eq!(serde_json::to_value(&probability).unwrap()["probability_anywhere"], "3/8");
+    assert_eq!(serde_json::to_value(&probability).unwrap()["expected_pages_at_start"], "4/1");
+}
@@ -0,0 +1,71 @@
+use akshara_mantapa::{
+    random_below, seeded_rng, LibraryGeometry, LibraryOfBabel, Level, Location,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+
+#[test]
+fn random_below_stays_below_large_bounds() {
+    let mut rng = seeded_rng(1);
+    let library = LibraryOfBabel::new();
+
+    // The library's N^400 (over 6,000 bits), and a bound just past a power of
+    // two, where most draws are rejected
+    let just_past = (BigUint::one() << 6000u32) + BigUint::one();
+    for bound in [library.page_count().clone(), just_past] {
+        assert!(bound.bits() > 6000);
+        let samples: Vec<BigUint> = (0..200).map(|_| random_below(&mut rng, &bound)).collect();
+        assert!(samples.iter().all(|sample| *sample < bound));
+        // Uniform draws land in the upper half about half of the time
+        let upper = samples.iter().filter(|sample| **sample >= &bound >> 1u32).count();
+        assert!((60..=140).contains(&upper), "{} of 200 in the upper half", upper);
+    }
+
+    assert_eq!(random_below(&mut rng, &BigUint::one()), BigUint::default());
+    assert_eq!(random_below(&mut rng, &BigUint::default()), BigUint::default());
+}
+
+#[test]
+fn random_pages_reach_rooms_beyond_u64() {
+    let library = LibraryOfBabel::new();
+    let mut rng = seeded_rng(2);
+    let beyond_u64 = BigUint::from(u64::MAX);
+
+    for _ in 0..50 {
+        let location = library.random_location_with(&mut rng);
+        let mandira = &location.hierarchical.mandira;
+        assert!(*mandira > beyond_u64, "room {} fits in a u64", mandira);
+        assert!(*mandira < library.room_count());
+    }
+}
+
+#[test]
+fn random_location_in_stays_within_scope() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let geometry = library.geometry();
+    let mut rng = seeded_rng(3);
+
+    let last = Location::from_raw_address(&(library.page_count() - BigUint::one()), geometry);
+    for scope in [library.random_location_with(&mut rng), last] {
+        let s = &scope.hierarchical;
+        for level in [Level::Puta, Level::Pustaka, Level::Patti, Level::Gode, Level::Mandira] {
+            for _ in 0..20 {
+                let location = library.random_location_in_with(&scope, level, &mut rng).unwrap();
+                let h = &location.hierarchical;
+                assert!(location.to_raw() < *library.page_count());
+
+                // Every level above the one chosen is kept
+                assert_eq!(h.mandira, s.mandira);
+                let kept = match level {
+                    Level::Mandira => 0,
+                    Level::Gode => 1,
+                    Level::Patti => 2,
+                    Level::Pustaka => 3,
+                    Level::Puta => 4,
+                };
+                let fixed = [h.gode == s.gode, h.patti == s.patti, h.pustaka == s.pustaka, h.puta == s.puta];
+                assert!(fixed[..kept].iter().all(|&same| same), "{:?} left {:?}", level, s);
+            }
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use akshara_mantapa::{
+    seeded_rng, CoherenceModel, GraphemeAlphabet, LibraryGeometry, LibraryOfBabel, WordMatch,
+};
+
+const WORDS: &str = "\
+# Kannada words, some with counts
+ಕನ್ನಡ
+ಕನ್ನಡಿಗ 5
+ಮನೆ 2
+ಅ
+library
+";
+
+#[test]
+fn finds_longest_words_left_to_right() {
+    let alphabet = GraphemeAlphabet::new();
+    let model = CoherenceModel::from_word_list(WORDS, &alphabet);
+
+    // Comments, single clusters and words outside the alphabet don't count
+    assert_eq!(model.word_count(), 3);
+
+    let page = alphabet.segment("ಕನ್ನಡಿಗ ಮನೆ ಕನ್ನಡ ಅ ಕನ").unwrap();
+    let score = model.score(&page, &alphabet);
+    let word = |position, cluster_count, text: &str| WordMatch {
+        position,
+        cluster_count,
+        text: text.to_string(),
+    };
+    assert_eq!(
+        score.words,
+        vec![word(0, 4, "ಕನ್ನಡಿಗ"), word(5, 2, "ಮನೆ"), word(8, 3, "ಕನ್ನಡ")],
+    );
+    assert_eq!(score.word_clusters, 9);
+
+    let random = alphabet.segment("ಙಝಞಢ").unwrap();
+    assert!(model.score(&random, &alphabet).words.is_empty());
+    assert!(score.bits_per_cluster > model.score(&random, &alphabet).bits_per_cluster);
+}
+
+#[test]
+fn coherent_pages_are_the_best_samples_in_order() {
+    let library = LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet());
+    let model = CoherenceModel::from_word_list(WORDS, library.alphabet());
+
+    let best = library.find_coherent_pages_with(&model, 200, 5, &mut seeded_rng(7));
+    assert_eq!(best.len(), 5);
+    for pair in best.windows(2) {
+        assert!(pair[0].score.compare(&pair[1].score).is_ge());
+    }
+
+    // The same seed draws the same pages; ranking them all gives the same top five
+    let mut rng = seeded_rng(7);
+    let mut all: Vec<_> = (0..200)
+        .map(|_| {
+            let page = library.random_page_with(&mut rng);
+            let score = library.score_page(&page, &model);
+            (page.location, score)
+        })
+        .collect();
+    all.sort_by(|a, b| b.1.compare(&a.1));
+    let expected: Vec<_> = all[..5].iter().map(|(location, _)| location).collect();
+    let actual: Vec<_> = best.iter().map(|page| &page.location).collect();
+    assert_eq!(actual, expected);
+
+    let all_kept = library.find_coherent_pages_with(&model, 3, 10, &mut seeded_rng(7));
+    assert_eq!(all_kept.len(), 3);
+}
@@ -0,0 +1,71 @@
+use std::collections::HashSet;
+
+use akshara_mantapa::{
+    Direction, GalleryTopology, HierarchicalAddress, LibraryGeometry, LibraryOfBabel, Location,
+};
+use num_bigint::BigUint;
+use num_traits::One;
+
+fn rooms(library: &LibraryOfBabel) -> [BigUint; 4] {
+    let count = library.room_count();
+    [BigUint::default(), BigUint::one(), &count >> 1u32, count - BigUint::one()]
+}
+
+#[test]
+fn every_direction_has_a_way_back() {
+    let libraries = [LibraryOfBabel::new(), LibraryOfBabel::with_geometry(LibraryGeometry::pamphlet())];
+    for library in libraries {
+        for mandira in rooms(&library) {
+            let neighbours = library.neighbours(&mandira);
+            let all = [&neighbours.up, &neighbours.down, &neighbours.left, &neighbours.right];
+
+            let distinct: HashSet<_> = all.iter().copied().chain([&mandira]).collect();
+            assert_eq!(distinct.len(), 5, "neighbours of {} repeat", mandira);
+
+            let h = HierarchicalAddress { mandira: mandira.clone(), gode: 1, patti: 1, pustaka: 1, puta: 1 };
+            let start = Location::from_hierarchical(h, library.geometry()).unwrap();
+            for direction in Direction::all() {
+                let there = library.walk(&start, direction);
+                assert!(all.contains(&&there.hierarchical.mandira));
+                assert!(there.hierarchical.mandira < library.room_count());
+
+                let back = library.walk(&there, direction.opposite());
+                assert_eq!(back.hierarchical.mandira, mandira, "{:?} and back", direction);
+                assert_eq!(back.hierarchical.puta, 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn small_libraries_connect_every_room() {
+    for count in 1..=40u32 {
+        let topology = GalleryTopology::new(BigUint::from(count));
+        let rooms: Vec<BigUint> = (0..count).map(BigUint::from).collect();
+
+        for room in &rooms {
+            for direction in Direction::all() {
+                let there = topology.neighbour(room, direction);
+                assert!(there < BigUint::from(count));
+                assert_eq!(&topology.neighbour(&there, direction.opposite()), room);
+            }
+            // Below five rooms, and with six, there are too few coprime steps
+            if count > 4 && count != 6 {
+                let n = topology.neighbours(room);
+                let distinct: HashSet<_> = [&n.up, &n.down, &n.left, &n.right, room].into_iter().collect();
+                assert_eq!(distinct.len(), 5, "room {} of {}", room, count);
+            }
+        }
+
+        // The staircase alone reaches every room before coming back
+        for direction in [Direction::Up, Direction::Right] {
+            let mut seen = HashSet::new();
+            let mut room = BigUint::default();
+            while seen.insert(room.clone()) {
+                room = topology.neighbour(&room, direction);
+            }
+            assert_eq!(room, BigUint::default());
+            assert_eq!(seen.len(), count as usize, "{:?} in {} rooms", direction, count);
+        }
+    }
+}
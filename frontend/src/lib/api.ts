@@ -21,6 +21,14 @@ export interface Page {
 	hierarchical: HierarchicalDisplay;
 	content: string;
 	formatted_content: string;
+	lines?: LineSpan[];            // Cluster range and formatted_content offsets of each line
+}
+
+export interface LineSpan {
+	start: number;                 // Clusters start..end are on this line
+	end: number;
+	char_start: number;            // Offsets into formatted_content
+	char_end: number;
 }
 
 export interface LocationResponse {
@@ -28,11 +36,31 @@ export interface LocationResponse {
 	hierarchical: HierarchicalDisplay;
 }
 
+export interface Book {
+	location: LocationResponse;
+	title: string;
+	pages: Page[];
+}
+
+export interface MatchSpan {
+	position: number;              // Cluster offset of the match
+	cluster_count: number;
+	char_start: number;            // Offsets into content
+	char_end: number;
+	byte_start: number;
+	byte_end: number;
+	line: number;                  // 0-based line in formatted_content
+	column: number;                // 0-based cluster column in that line
+	formatted_char_start: number;  // Offsets into formatted_content
+	formatted_char_end: number;
+}
+
 export interface SearchResponse {
 	query: string;
 	found: boolean;
 	location?: LocationResponse;
 	page_preview?: string;
+	span?: MatchSpan;
 }
 
 // WASM library singleton
@@ -167,3 +195,23 @@ export async function searchTextRandom(query: string): Promise<SearchResponse> {
 		return response.json();
 	}
 }
+
+export async function getBook(address: string): Promise<Book> {
+	if (USE_WASM) {
+		const lib = await getWasmLibrary();
+		const result = JSON.parse(lib.getBook(address));
+		if (!result.success) {
+			throw new Error(result.error || 'Failed to get book');
+		}
+		return {
+			location: result.location,
+			title: result.title,
+			pages: result.pages
+		};
+	} else {
+		const params = new URLSearchParams({ address });
+		const response = await fetch(`${API_BASE}/book?${params}`);
+		if (!response.ok) throw new Error('Failed to fetch book');
+		return response.json();
+	}
+}